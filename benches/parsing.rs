@@ -0,0 +1,38 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use gh_jobs_purge::{parse_rate_limit, parse_run_ids};
+
+/// `gh run list` output for a large repository: one run ID per line
+fn run_id_list(lines: usize) -> String {
+    (0..lines)
+        .map(|i| format!("{}\n", 9_000_000_000_i64 + i as i64))
+        .collect()
+}
+
+/// A `gh api rate_limit --jq .resources.core` payload padded with extra fields
+fn large_rate_limit_payload() -> Vec<u8> {
+    let mut payload = String::from(r#"{"remaining":4999,"reset":1706515200"#);
+    for i in 0..1_000 {
+        payload.push_str(&format!(r#","extra_{}":"{}""#, i, "x".repeat(32)));
+    }
+    payload.push('}');
+    payload.into_bytes()
+}
+
+fn bench_parse_run_ids(c: &mut Criterion) {
+    let output = run_id_list(10_000);
+    c.bench_function("parse_run_ids 10k lines", |b| {
+        b.iter(|| parse_run_ids(black_box(&output)).unwrap())
+    });
+}
+
+fn bench_parse_rate_limit(c: &mut Criterion) {
+    let payload = large_rate_limit_payload();
+    c.bench_function("parse_rate_limit large payload", |b| {
+        b.iter(|| parse_rate_limit(black_box(&payload)).unwrap())
+    });
+}
+
+criterion_group!(benches, bench_parse_run_ids, bench_parse_rate_limit);
+criterion_main!(benches);
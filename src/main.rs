@@ -1,18 +1,200 @@
-use std::{process::Command, thread, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, btree_map::Entry},
+    fs,
+    io::{self, IsTerminal, Write},
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex, OnceLock, PoisonError,
+        atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use colored::*;
 use gh_jobs_purge::{
-    calculate_wait_seconds, check_for_secondary_rate_limit, check_rate_limit,
-    fetch_runs_with_statuses, parse_and_validate_statuses, should_hibernate,
+    ActionsWriteAccess, BatchDecision, CONFIRM_EMPTY_DELAY, CommandEnv, ConfigFormat,
+    ConnectionTestResult, DEFAULT_CLOCK_SKEW_WAIT_SECONDS, DEFAULT_DELETE_TIMEOUT_SECONDS,
+    DEFAULT_HIBERNATION_CAP_SECONDS, DEFAULT_LOG_MAX_SIZE, DeleteOrder, DryRunReport,
+    FALLBACK_WAIT_SECONDS, GhExitError, GithubActionsContext, GroupedConcurrencyPool,
+    HIBERNATE_THRESHOLD, HardTimeout, HealthServer, IdlePollOptions, IdleResult, JobChecks,
+    MAX_JOBS, MAX_MALFORMED_RATE_LIMIT_RETRIES, MatchedRunTableRow, OutputFormat,
+    PERMISSION_EXIT_CODE, PR_LIST_LIMIT, PaginationState, PullRequestRef, PurgeProgress,
+    PurgeReport, PushGatewayClient, QUOTA_EXIT_CODE, RUN_LIST_JSON_FIELDS, RUN_LIST_LIMIT,
+    RUNS_PER_PAGE, RateLimitCore, RunTableRow, Severity, Stats, StatusCountRow, StatusFileFormat,
+    StatusProgress, SummaryFormat, TableStyle, TokenBucket, ToolPhase, ToolState, WorkflowRun,
+    WorkflowTableRow, age_histogram, api_calls_used, calls_above_reserve, cancel_run,
+    capped_hibernation_duration, check_actions_write_permission, check_for_secondary_rate_limit,
+    check_gh_compatibility, check_rate_limit, clock_skew_wait, compute_auto_threshold,
+    compute_safe_batch_size, count_runs_by_workflow, delete_cache, deletion_summary,
+    detect_gh_version, dir_size, disable_workflow, effective_jobs, expand_repo_pattern,
+    fetch_active_workflows, fetch_branch_runs, fetch_closed_pr_branches, fetch_default_branch,
+    fetch_orphaned_runs, fetch_pr_runs, fetch_pull_request, fetch_pull_requests, fetch_run_details,
+    fetch_run_json, fetch_run_records_limit, fetch_runs_count_only, fetch_runs_paginated,
+    fetch_runs_with_statuses_limit, fetch_workflow_run_count, fetch_workflows, fill_workflow_paths,
+    filter_by_latest_per_commit, filter_by_run_name, filter_by_workflow_path, filter_caches,
+    filter_ids_by_regex, filter_runs_with_jq, format_gh_command, format_reset_time,
+    format_status_counts, gh_command, gh_exit_error, group_runs_by_workflow, group_stats_by_owner,
+    is_affirmative, is_malformed_rate_limit, json_to_toml, list_caches, order_runs,
+    parse_batch_decision, parse_repo_pattern, parse_statuses, poll_until_idle,
+    pr_branches_to_purge, print_test_results, prometheus_metrics, quota_consumed,
+    random_jitter_seconds, record_failed_attempts, redundant_statuses, remove_protected_runs,
+    render_table, reset_wait_seconds, retain_runs_without_jobs, run_hook, run_in_worker_pool,
+    run_matches_statuses, runs_by_matched_status, runtime_statuses_in, sample_evenly, save_run_log,
+    set_warning_handler, should_hibernate, simulate_rate_limit, status_color, timed_command,
+    truncated_id_list, validate_gh_cli, validate_proxy_url, wait_for_cancellation,
+    workflows_with_active_runs,
 };
 use rayon::prelude::*;
+use serde::Serialize;
+
+/// Set by --output-ids-only: decorative output then goes to stderr
+static CHATTER_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Runs deleted by this process, reported in the --status-file
+static DELETED_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// Run deletions that failed in this process, reported by --stats-interval
+static FAILED_DELETES: AtomicUsize = AtomicUsize::new(0);
+
+/// Pagination of the REST runs listing, seeded from --start-cursor
+static PAGINATION: Mutex<PaginationState> = Mutex::new(PaginationState::new(None));
+
+/// Set by --compact: decorative output is replaced by one line per repository
+static COMPACT: AtomicBool = AtomicBool::new(false);
+
+/// The --health-check-port endpoint, once started
+static HEALTH: OnceLock<HealthServer> = OnceLock::new();
+
+/// Set once a fetch returns matching runs, for --on-empty-exit-code
+static FOUND_RUNS: AtomicBool = AtomicBool::new(false);
+
+/// Runs annotated by --annotate-surviving-runs with their note, so each is noted only once
+///
+/// Copied into the --report file by [`record_retained`].
+static ANNOTATED_RUNS: Mutex<BTreeMap<i64, String>> = Mutex::new(BTreeMap::new());
+
+/// Runs checked for jobs under --delete-runs-without-jobs, across fetches and repositories
+static JOB_CHECKS: Mutex<JobChecks> = Mutex::new(JobChecks::new());
+
+/// Remaining quota below which the purge hibernates, see --auto-threshold
+static HIBERNATE_AT: AtomicI32 = AtomicI32::new(HIBERNATE_THRESHOLD);
+
+/// Print decorative output: stdout normally, stderr with --output-ids-only, nowhere with --compact
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !COMPACT.load(Ordering::Relaxed) {
+            if CHATTER_TO_STDERR.load(Ordering::Relaxed) {
+                eprintln!($($arg)*)
+            } else {
+                println!($($arg)*)
+            }
+        }
+    };
+}
+
+/// Print a message styled by its [`Severity`]
+///
+/// Info is progress output like `say!`; warnings and errors always go to stderr, even with
+/// --compact.
+macro_rules! log {
+    ($severity:expr, $($arg:tt)*) => {{
+        let severity: Severity = $severity;
+        let line = styled(severity, &format!($($arg)*));
+        if severity == Severity::Info {
+            say!("{}", line)
+        } else {
+            eprintln!("{}", line)
+        }
+    }};
+}
+
+/// A message with the icon, label and colour of its severity; errors are also bold
+fn styled(severity: Severity, message: &str) -> String {
+    let color = severity.color();
+    let label = format!("{}:", severity.label()).color(color).bold();
+    let message = match severity {
+        Severity::Error => message.color(color).bold(),
+        _ => message.color(color),
+    };
+    format!("{} {} {}", severity.icon(), label, message)
+}
+
+/// What to do when active runs are still present after `--wait-timeout`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum WaitTimeoutAction {
+    /// Stop without deleting anything
+    Abort,
+    /// Delete anyway
+    Proceed,
+}
+
+/// Read-only subcommands; without one, matching runs are purged
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+enum Commands {
+    /// List the runs matching the status filter without deleting them
+    List,
+    /// Show how many runs match each status without deleting them
+    Stats,
+    /// List the repository's workflows (ID, name, file path and state)
+    Workflows,
+    /// Count the runs that would be deleted without deleting them
+    Count {
+        /// Break the count down per workflow, noisiest first
+        #[arg(long)]
+        count_by_workflow: bool,
+    },
+    /// Check gh, the API quota and run listing access without modifying anything
+    ///
+    /// Exits with a non-zero code if any check fails.
+    TestConnection,
+    /// Delete GitHub Actions caches instead of runs
+    PurgeCaches {
+        /// Only delete caches whose key matches this glob (e.g. 'cargo-*')
+        #[arg(long, value_name = "GLOB")]
+        cache_key_pattern: Option<String>,
+        /// Only delete caches not used in the last N days
+        #[arg(long, value_name = "N")]
+        cache_older_than_days: Option<u32>,
+        /// Only delete caches of this ref (a branch name or a full ref like refs/pull/42/merge)
+        #[arg(long, value_name = "REF")]
+        cache_ref: Option<String>,
+    },
+}
 
 /// GitHub Actions workflow run purge tool
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Serialize)]
 #[command(author, version, about, long_about = None)]
+#[serde(rename_all = "kebab-case")]
 struct Args {
+    #[command(subcommand)]
+    #[serde(skip)]
+    command: Option<Commands>,
+
+    /// Print the effective settings (after defaults and normalization) and exit
+    ///
+    /// The output can be saved for reference or to rebuild the same invocation.
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    #[serde(skip)]
+    export_config: Option<ConfigFormat>,
+
+    /// Print the gh commands a purge with these settings would run, and exit without running any
+    ///
+    /// Values only known at run time appear as placeholders like <RUN_ID>.
+    #[arg(long)]
+    #[serde(skip)]
+    explain: bool,
+
+    /// Explain what deleting a run cleans up (and what it cannot) and exit
+    #[arg(long)]
+    #[serde(skip)]
+    explain_cleanup: bool,
+
     /// Comma-separated list of statuses to filter runs
     ///
     /// Runtime statuses (active runs):
@@ -27,151 +209,3002 @@ struct Args {
     ///
     /// Note: Use dashes (-) or underscores (_) interchangeably (e.g.,
     /// in-progress or in_progress)
-    #[arg(short, long, default_value = "completed", value_name = "STATUS")]
+    #[arg(
+        short,
+        long,
+        default_value = "completed",
+        value_name = "STATUS",
+        global = true
+    )]
     status: String,
+
+    /// Pass unrecognized statuses through to gh instead of rejecting them
+    ///
+    /// For statuses GitHub added after this release. Dashes are still normalized to
+    /// underscores.
+    #[arg(long)]
+    skip_status_validation: bool,
+
+    /// Safe daily cleanup: only delete skipped and neutral runs
+    ///
+    /// Shorthand for --status skipped,neutral. Those runs did no work, so deleting them never
+    /// loses a meaningful result.
+    #[arg(long, conflicts_with_all = ["status", "cancel_then_delete"])]
+    delete_skipped_and_neutral_runs: bool,
+
+    /// Only delete runs whose workflow was deleted or disabled
+    ///
+    /// Lists every run matching the status filter, groups them by workflow ID and keeps the ones
+    /// whose workflow is no longer active in the repository.
+    #[arg(long)]
+    orphaned_workflows: bool,
+
+    /// Only delete pull_request runs of merged or closed pull requests
+    ///
+    /// Runs of the default branch and of branches that an open pull request still uses are
+    /// kept. Combines with --status.
+    #[arg(long, conflicts_with_all = ["orphaned_workflows", "cancel_then_delete"])]
+    resolve_pr_runs: bool,
+
+    /// Only delete runs (of any event) on the branches of closed or merged pull requests
+    ///
+    /// Like --resolve-pr-runs, the default branch and branches that an open pull request still
+    /// uses are kept. Combines with --status.
+    #[arg(long, conflicts_with_all = ["orphaned_workflows", "cancel_then_delete", "resolve_pr_runs"])]
+    pr_cleanup: bool,
+
+    /// Only delete the pull_request runs of this pull request (matching --status)
+    ///
+    /// The pull request's head branch is looked up first, so an unknown number fails before
+    /// anything is deleted.
+    #[arg(
+        long,
+        value_name = "NUMBER",
+        conflicts_with_all = ["orphaned_workflows", "cancel_then_delete", "resolve_pr_runs", "pr_cleanup"]
+    )]
+    pr: Option<u64>,
+
+    /// With --pr-cleanup, only include pull requests closed more than N days ago
+    #[arg(long, value_name = "N", requires = "pr_cleanup")]
+    pr_closed_days: Option<u32>,
+
+    /// Only delete runs superseded by a newer run of the same workflow for the same commit
+    ///
+    /// The newest selected run of each (commit, workflow) pair is kept.
+    #[arg(long)]
+    dedup_by_commit: bool,
+
+    /// Only delete "ghost" runs that have no jobs (e.g. from workflow syntax errors)
+    ///
+    /// Checking a run costs one API call; see --without-jobs-check-limit.
+    #[arg(long)]
+    delete_runs_without_jobs: bool,
+
+    /// Check at most N candidate runs for jobs under --delete-runs-without-jobs
+    ///
+    /// The cap counts for the whole invocation; each run is checked only once.
+    #[arg(long, value_name = "N", default_value_t = 50)]
+    without_jobs_check_limit: usize,
+
+    /// Order in which the selected runs are deleted (and listed by --dry-run)
+    #[arg(long, value_enum, default_value_t = DeleteOrder::Oldest)]
+    delete_order: DeleteOrder,
+
+    /// Seed for --delete-order random, making the order reproducible
+    #[arg(long, value_name = "N")]
+    seed: Option<u64>,
+
+    /// Keep runs in the order gh lists them (newest first) instead of sorting them by ID
+    #[arg(long, conflicts_with_all = ["delete_order", "seed"])]
+    no_sort: bool,
+
+    /// Only delete runs whose workflow file path matches this glob
+    ///
+    /// E.g. '.github/workflows/release-*.yml'. Costs one extra API call per fetch to look up
+    /// the workflow files.
+    #[arg(long, value_name = "GLOB")]
+    workflow_path_glob: Option<String>,
+
+    /// Only delete runs whose display title (commit message or PR title) contains this text
+    ///
+    /// Case-insensitive; combines with --status.
+    #[arg(long, value_name = "TEXT")]
+    title_contains: Option<String>,
+
+    /// Only delete runs whose run name (from `run-name:`, else the workflow name) matches
+    ///
+    /// A case-insensitive substring, or a regular expression when it starts with '^'.
+    #[arg(long, value_name = "PATTERN")]
+    run_name_filter: Option<String>,
+
+    /// Only delete runs whose ID (as a decimal string) matches this regular expression
+    ///
+    /// Unanchored, e.g. '^1234' for IDs starting with 1234.
+    #[arg(long, value_name = "REGEX")]
+    include_run_ids_matching: Option<String>,
+
+    /// Never delete runs whose ID (as a decimal string) matches this regular expression
+    #[arg(long, value_name = "REGEX")]
+    exclude_run_ids_matching: Option<String>,
+
+    /// Only delete runs for which this jq condition holds on their REST API JSON
+    ///
+    /// E.g. '.conclusion == "failure" and .run_attempt > 3'. The JSON of each run the other
+    /// filters kept is fetched by ID (one API call per run); requires jq. See the README for
+    /// the fields.
+    #[arg(long, value_name = "EXPR")]
+    delete_runs_matching_jq: Option<String>,
+
+    /// Never delete runs of this workflow (by name), whatever the other filters select
+    ///
+    /// Can be given several times.
+    #[arg(long, value_name = "NAME")]
+    protect_workflow: Vec<String>,
+
+    /// Annotate the fetched runs that the filters kept with TEXT (e.g. "reviewed, retained")
+    ///
+    /// GitHub has no API for annotating runs, so each kept run is recorded once with the note
+    /// under "retained" in the --report file.
+    #[arg(long, value_name = "TEXT", requires = "report")]
+    annotate_surviving_runs: Option<String>,
+
+    /// Skip the runs of workflows that currently have a queued or in-progress run
+    #[arg(long)]
+    skip_if_workflow_running: bool,
+
+    /// Sleep a random number of seconds, up to this maximum, before the first API call
+    ///
+    /// Spreads the load of many scheduled invocations that all start at the same time.
+    #[arg(long, value_name = "MAX_SECS", default_value_t = 0)]
+    jitter_start: u64,
+
+    /// Repository to purge (OWNER/NAME); defaults to the repository of the current directory
+    #[arg(
+        short = 'R',
+        long,
+        value_name = "OWNER/NAME",
+        conflicts_with = "repo_pattern"
+    )]
+    repo: Option<String>,
+
+    /// Purge every repository of an organization whose name matches a glob (e.g. acme/service-*)
+    #[arg(long, value_name = "OWNER/GLOB")]
+    repo_pattern: Option<String>,
+
+    /// With --repo-pattern, include private repositories (only private ones if given alone)
+    #[arg(long, requires = "repo_pattern")]
+    include_private: bool,
+
+    /// With --repo-pattern, include public repositories (only public ones if given alone)
+    #[arg(long, requires = "repo_pattern")]
+    include_public: bool,
+
+    /// Wait until no runs are queued or in progress before purging
+    #[arg(long)]
+    wait_for_in_progress: bool,
+
+    /// Maximum number of seconds to wait with --wait-for-in-progress
+    #[arg(long, value_name = "SECS", default_value_t = 300)]
+    wait_timeout: u64,
+
+    /// What to do when runs are still active after --wait-timeout
+    #[arg(long, value_enum, default_value_t = WaitTimeoutAction::Abort)]
+    wait_timeout_action: WaitTimeoutAction,
+
+    /// Ask before deleting each batch whether to proceed, skip it (its runs are then left alone)
+    /// or abort
+    #[arg(long)]
+    prompt_per_batch: bool,
+
+    /// Print the number of matching runs per status before deleting (one API call per status)
+    ///
+    /// With --prompt-per-batch, the counts are part of the first prompt.
+    #[arg(long)]
+    status_counts_before_delete: bool,
+
+    /// Refuse to purge with runtime statuses (queued, in-progress, ...) or redundant statuses
+    /// instead of warning, and re-check each run's status right before deleting it
+    ///
+    /// Deleting an active run aborts it, which is rarely what a cleanup means to do. Use
+    /// --cancel-then-delete or --include-active to get rid of active runs on purpose. The
+    /// re-check skips runs that changed state since they were listed, at the cost of one extra
+    /// API call per run.
+    #[arg(long)]
+    strict: bool,
+
+    /// Resume an interrupted paginated listing from the cursor it printed (STATUS:PAGE)
+    ///
+    /// Applies to the REST listings (--orphaned-workflows, list, count); after each page the
+    /// cursor to resume from is printed to stderr.
+    #[arg(long, value_name = "TOKEN")]
+    start_cursor: Option<String>,
+
+    /// Checkpoint the deleted run IDs to this JSON file after every batch
+    #[arg(long, value_name = "PATH")]
+    report: Option<PathBuf>,
+
+    /// Continue an interrupted purge: skip the runs a previous --report recorded as deleted
+    ///
+    /// Warns when the report was written for other repositories or another status filter.
+    #[arg(long, value_name = "PATH")]
+    resume_from_report: Option<PathBuf>,
+
+    /// Keep the current state (STARTING, FETCHING, DELETING, HIBERNATING, DONE) in this file
+    #[arg(long, value_name = "PATH")]
+    status_file: Option<PathBuf>,
+
+    /// Format of the --status-file
+    #[arg(long, value_enum, default_value_t = StatusFileFormat::Text, requires = "status_file")]
+    status_file_format: StatusFileFormat,
+
+    /// Serve GET /health on this port (200, or 503 while hibernating) for liveness probes
+    ///
+    /// Listens on all interfaces, as probes from outside a pod require; see
+    /// --health-check-bind.
+    #[arg(long, value_name = "PORT")]
+    health_check_port: Option<u16>,
+
+    /// Address the --health-check-port endpoint listens on, e.g. 127.0.0.1 for local probes only
+    #[arg(
+        long,
+        value_name = "ADDR",
+        default_value = "0.0.0.0",
+        requires = "health_check_port"
+    )]
+    health_check_bind: IpAddr,
+
+    /// After purging, list active workflows that have no runs left and offer to disable them
+    ///
+    /// Disabling asks for confirmation first; with --dry-run the workflows are only listed.
+    #[arg(long)]
+    delete_empty_workflows: bool,
+
+    /// Only conclude that no runs are left after N consecutive empty fetches
+    ///
+    /// The run listing is eventually consistent and can briefly come back empty while runs
+    /// remain. Fetches are 5 seconds apart.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    confirm_empty: u32,
+
+    /// Number of parallel workers for deletions
+    ///
+    /// At most four times as many deletions are queued at once. Values above 50 are capped to 50
+    /// (with a warning) unless --force-high-concurrency is given.
+    #[arg(
+        long,
+        default_value_t = 15,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    jobs: usize,
+
+    /// Allow --jobs above 50, at the risk of secondary rate limits and exhausting the host
+    #[arg(long)]
+    force_high_concurrency: bool,
+
+    /// Print "N/M deleted, X failures, Y/sec" every SECS seconds while a batch is deleted
+    ///
+    /// Only when the progress output is a terminal.
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    stats_interval: Option<u64>,
+
+    /// Kill a single gh run delete that has not finished after this many seconds
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = DEFAULT_DELETE_TIMEOUT_SECONDS,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    delete_timeout: u64,
+
+    /// Never delete more than N runs per minute, whatever the parallelism
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    runs_per_minute: Option<u32>,
+
+    /// Delete at most N runs of any one workflow at once, for per-workflow limits
+    ///
+    /// Runs are grouped by workflow name; unlimited by default.
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    per_workflow_concurrency: Option<usize>,
+
+    /// Exit with code 6 after this many seconds, wherever the tool is at
+    ///
+    /// A hard deadline for schedulers: it also interrupts hibernation and hung gh calls.
+    #[arg(long, value_name = "SECS")]
+    timeout: Option<u64>,
+
+    /// Stop cleanly (exit code 0) once this many gh API calls were made
+    ///
+    /// Every gh invocation counts as one call, including rate limit checks. The work left
+    /// undone is reported.
+    #[arg(long, value_name = "N", visible_alias = "max-api-calls")]
+    max_total_api_calls: Option<usize>,
+
+    /// Exit with this code (0-125) when no matching runs were found at all
+    #[arg(
+        long,
+        value_name = "CODE",
+        default_value_t = 0,
+        value_parser = clap::value_parser!(u8).range(0..=125)
+    )]
+    on_empty_exit_code: u8,
+
+    /// Pretend the API quota has REMAINING calls left, resetting in 5 minutes, without asking gh
+    ///
+    /// After the reset, a full quota of 5000 is reported. For testing hibernation (below 50)
+    /// and the resume after it together with --dry-run.
+    #[arg(long, value_name = "REMAINING", allow_negative_numbers = true)]
+    simulate_rate_limit: Option<i32>,
+
+    /// Hibernate below PERCENT of the hourly quota instead of below 50 remaining requests
+    ///
+    /// E.g. 1 hibernates below 600 on a 60 000 requests/hour GitHub Enterprise quota. The
+    /// threshold is computed once at startup and is at least 10.
+    #[arg(long, value_name = "PERCENT", value_parser = parse_percent)]
+    auto_threshold: Option<f64>,
+
+    /// Refuse to start, exiting with code 8, when fewer than N requests remain
+    ///
+    /// Lets a scheduled job retry later instead of starting only to hibernate right away.
+    #[arg(long, value_name = "N")]
+    require_quota: Option<i32>,
+
+    /// List only as many runs per status as the quota can delete before hibernating
+    ///
+    /// Computed from the remaining quota at each fetch (one call per deletion), at most 300.
+    #[arg(long)]
+    adaptive_limit: bool,
+
+    /// Stop cleanly (exit code 0) instead of letting the remaining quota drop below N
+    ///
+    /// Unlike hibernation, which only pauses, this keeps N requests free for other tools using
+    /// the same token.
+    #[arg(long, value_name = "N")]
+    min_remaining_after: Option<u32>,
+
+    /// Also delete runs that are still active (queued, in progress, ...), aborting them
+    ///
+    /// Without it, active runs matched by the filter are skipped with a warning.
+    #[arg(long)]
+    include_active: bool,
+
+    /// Cancel queued and in-progress runs, wait for them to stop, then delete them
+    ///
+    /// Replaces --status with "in_progress,queued".
+    #[arg(long, conflicts_with_all = ["status", "orphaned_workflows"])]
+    cancel_then_delete: bool,
+
+    /// Maximum number of seconds to wait for each run to stop with --cancel-then-delete
+    #[arg(long, value_name = "SECS", default_value_t = 120)]
+    cancel_timeout: u64,
+
+    /// Longest single hibernation nap; the quota is re-checked after each one
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_HIBERNATION_CAP_SECONDS)]
+    hibernate_cap: u64,
+
+    /// Least time to hibernate when the quota is exhausted but its reset time already passed
+    ///
+    /// That happens when the local clock is ahead of GitHub's; waiting avoids retrying at once
+    /// against a quota that was not reset yet.
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = DEFAULT_CLOCK_SKEW_WAIT_SECONDS,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    clock_skew_wait: u64,
+
+    /// Show rate limit reset times in UTC instead of the local timezone
+    #[arg(long)]
+    utc: bool,
+
+    /// Command run (via `sh -c`) before deleting each run; a non-zero exit skips the run
+    ///
+    /// The run ID and repository are passed as $GH_RUN_ID and $GH_REPO. Hooks run one at a time
+    /// even though deletions are parallel.
+    #[arg(long, value_name = "COMMAND")]
+    pre_delete_hook: Option<String>,
+
+    /// Command run (via `sh -c`) after each successful deletion, with $GH_RUN_ID and $GH_REPO
+    #[arg(long, value_name = "COMMAND")]
+    post_delete_hook: Option<String>,
+
+    /// Show the runs that would be deleted, after every filter, without deleting anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// With --dry-run or `list`, show a run once for every status of the filter it matched
+    ///
+    /// A diagnostic for overlapping statuses (e.g. completed and failure). Runs are still
+    /// deleted once.
+    #[arg(long, global = true)]
+    no_dedup: bool,
+
+    /// With --dry-run or `list`, show the details of N runs spread evenly over the eligible ones
+    ///
+    /// A quick way to check that the filters picked the right kind of runs on a large
+    /// repository. Does not affect the JSON dry-run report.
+    #[arg(long, value_name = "N", global = true)]
+    sample: Option<usize>,
+
+    /// Proxy (http:// or https://) used for every gh call
+    #[arg(long, value_name = "URL")]
+    http_proxy: Option<String>,
+
+    /// Comma-separated hosts that bypass --http-proxy
+    #[arg(long, value_name = "HOSTS", requires = "http_proxy")]
+    no_proxy: Option<String>,
+
+    /// Take --repo, the token and the GitHub host from the GitHub Actions job environment
+    ///
+    /// Reads GITHUB_REPOSITORY (unless --repo or --repo-pattern is given), GITHUB_TOKEN and
+    /// GITHUB_API_URL. Automatic when GITHUB_ACTIONS=true; with this flag, a missing
+    /// GITHUB_REPOSITORY is an error.
+    #[arg(long)]
+    github_actions_context: bool,
+
+    /// Output format; with json, results go to stdout and progress messages to stderr
+    ///
+    /// Currently used by --dry-run, which then emits the eligible runs and a summary.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+    format: OutputFormat,
+
+    /// Print only the IDs of successfully deleted runs to stdout, one per line
+    ///
+    /// All other output goes to stderr, so the result can be piped into other tools.
+    #[arg(long)]
+    output_ids_only: bool,
+
+    /// Also print the IDs of the runs in each batch, and each failed deletion with its gh exit
+    /// code
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Collapse each repository's purge into one result line, updated in place on a terminal
+    #[arg(long, conflicts_with_all = ["prompt_per_batch", "delete_empty_workflows", "verbose"])]
+    compact: bool,
+
+    /// Give up on a run after this many failed deletions across fetch cycles
+    ///
+    /// Runs given up on are listed under "skipped" in the --report.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 3,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    max_retries_per_run: u32,
+
+    /// Request at most N pages per status when paging through runs via the REST API
+    ///
+    /// Bounds the cost of listing a huge repository with `list`, `count` or
+    /// --orphaned-workflows; a warning names the statuses that had more runs.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    max_list_pages: Option<u32>,
+
+    /// Skip re-fetching the eligible runs after a completed purge to verify none are left
+    #[arg(long)]
+    no_final_check: bool,
+
+    /// Format of the final summary of a purge
+    ///
+    /// Only the final summary: progress output stays human-readable. With json and toml the
+    /// summary is the only output on stdout, progress going to stderr. Defaults to human with
+    /// several repositories and to none with one.
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    summary_format: Option<SummaryFormat>,
+
+    /// With several repositories, print the final summary grouped by owner with subtotals
+    #[arg(long)]
+    group_repos_by_owner: bool,
+
+    /// Before deleting, check that the token may delete runs, exiting with code 7 if not
+    ///
+    /// On by default; pass --check-permissions false to skip the check (one API call per
+    /// repository).
+    #[arg(
+        long,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        default_value_t = true,
+        default_missing_value = "true"
+    )]
+    check_permissions: bool,
+
+    /// Colour status labels in run listings by status (default: on when colours are enabled)
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    colorize_status: Option<bool>,
+
+    /// How many run IDs --verbose prints per batch before summarizing the rest
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    truncate_run_id_output: usize,
+
+    /// Save each run's log (gh run view --log) before deleting it
+    ///
+    /// A run whose log cannot be saved is not deleted.
+    #[arg(long)]
+    tail_logs: bool,
+
+    /// Directory the --tail-logs files ({id}.log) are written to
+    #[arg(long, value_name = "DIR", default_value = "run-logs")]
+    log_output_dir: PathBuf,
+
+    /// Maximum size of a single saved log; longer logs are truncated
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_LOG_MAX_SIZE)]
+    log_max_size: u64,
+
+    /// Maximum total size of the log directory; purging stops once it is reached
+    #[arg(long, value_name = "BYTES")]
+    log_dir_max_size: Option<u64>,
+
+    /// Push the purge counters to this Prometheus Pushgateway after each batch
+    ///
+    /// Metrics go to {URL}/metrics/job/{JOB}/instance/{REPO}. Push failures are reported and
+    /// the purge continues.
+    #[arg(long, value_name = "URL")]
+    prometheus_push_gateway: Option<String>,
+
+    /// Value of the job label used with --prometheus-push-gateway
+    #[arg(long, value_name = "NAME", default_value = "gh-jobs-purge")]
+    prometheus_job_name: String,
+
+    /// With count or --dry-run, also print how many runs are from today, this week, this month
+    /// or older
+    #[arg(long, global = true)]
+    histogram: bool,
+
+    /// Table style used by the list and stats subcommands
+    #[arg(long, value_enum, default_value_t = TableStyle::Unicode, global = true)]
+    table_style: TableStyle,
+}
+
+impl Args {
+    /// Expand preset flags into the settings they stand for
+    fn resolve_presets(&mut self) {
+        if self.delete_skipped_and_neutral_runs {
+            self.status = "skipped,neutral".to_string();
+        }
+    }
+}
+
+/// How the purge of a repository ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PurgeOutcome {
+    /// No matching runs are left
+    Completed,
+    /// The user aborted from the per-batch prompt
+    Aborted,
+    /// --max-total-api-calls was reached
+    BudgetExhausted,
+    /// Going on would take the quota below --min-remaining-after
+    ReserveReached,
+}
+
+/// Fetch the runs to delete for this iteration
+///
+/// This is the single place where runs are selected: every filter must be applied here so that
+/// `--dry-run` previews exactly what a real purge would delete. `limit` caps the runs listed per
+/// status by the default listing. `pr` is the --pr pull request, looked up once by `run`.
+fn select_runs(
+    args: &Args,
+    statuses: &[String],
+    repo: Option<&str>,
+    pr: Option<&PullRequestRef>,
+    already_deleted: &BTreeSet<i64>,
+    limit: usize,
+) -> Result<Vec<WorkflowRun>> {
+    let mut runs = if args.orphaned_workflows {
+        select_orphaned_runs(statuses, repo)?
+    } else if args.resolve_pr_runs {
+        select_pr_runs(statuses, repo)?
+    } else if args.pr_cleanup {
+        select_pr_cleanup_runs(args, statuses, repo)?
+    } else if let Some(pr) = pr {
+        select_single_pr_runs(pr, statuses, repo)?
+    } else {
+        fetch_run_records_limit(statuses, repo, limit, !args.no_sort)?
+    };
+    // Runs recorded in the report (this session or the one being resumed) are not retried
+    runs.retain(|run| !already_deleted.contains(&run.id));
+    // Every listed run counts as reviewed, including those the filters below exclude
+    let fetched: Vec<i64> = runs.iter().map(|run| run.id).collect();
+
+    if let Some(needle) = args.title_contains.as_deref() {
+        runs.retain(|run| run.title_contains(needle));
+    }
+    if let Some(pattern) = args.run_name_filter.as_deref() {
+        let matching: HashSet<i64> = filter_by_run_name(&runs, pattern)?.into_iter().collect();
+        runs.retain(|run| matching.contains(&run.id));
+    }
+    if let Some(pattern) = args.include_run_ids_matching.as_deref() {
+        let ids: Vec<i64> = runs.iter().map(|run| run.id).collect();
+        let matching: HashSet<i64> = filter_ids_by_regex(&ids, pattern)?.into_iter().collect();
+        runs.retain(|run| matching.contains(&run.id));
+    }
+    if let Some(pattern) = args.exclude_run_ids_matching.as_deref() {
+        let ids: Vec<i64> = runs.iter().map(|run| run.id).collect();
+        let matching: HashSet<i64> = filter_ids_by_regex(&ids, pattern)?.into_iter().collect();
+        runs.retain(|run| !matching.contains(&run.id));
+    }
+    if let Some(pattern) = args.workflow_path_glob.as_deref() {
+        fill_workflow_paths(&mut runs, &fetch_workflows(repo)?);
+        let matching: HashSet<i64> = filter_by_workflow_path(&runs, pattern)?
+            .into_iter()
+            .collect();
+        runs.retain(|run| matching.contains(&run.id));
+    }
+    // Last of the listing filters: it costs one API call per remaining run
+    if let Some(expr) = args.delete_runs_matching_jq.as_deref()
+        && !runs.is_empty()
+    {
+        let mut runs_json = String::new();
+        for run in &runs {
+            runs_json.push_str(&fetch_run_json(run.id, repo)?);
+            runs_json.push('\n');
+        }
+        let matching: HashSet<i64> = filter_runs_with_jq(&runs_json, expr)?.into_iter().collect();
+        runs.retain(|run| matching.contains(&run.id));
+    }
+
+    // Before the active-run filter, so a newer run still in progress is the one kept
+    if args.dedup_by_commit {
+        let superseded: HashSet<i64> = filter_by_latest_per_commit(&runs).into_iter().collect();
+        runs.retain(|run| superseded.contains(&run.id));
+    }
+
+    if args.delete_runs_without_jobs {
+        let mut checks = JOB_CHECKS.lock().unwrap_or_else(PoisonError::into_inner);
+        let unchecked =
+            retain_runs_without_jobs(&mut runs, repo, &mut checks, args.without_jobs_check_limit)?;
+        if unchecked > 0 {
+            log!(
+                Severity::Warn,
+                "--without-jobs-check-limit of {} reached; {} run(s) were not checked for jobs.",
+                args.without_jobs_check_limit,
+                unchecked
+            );
+        }
+    }
+
+    // Deleting an active run aborts it: only do that when explicitly asked to
+    if !args.include_active && !args.cancel_then_delete {
+        runs.retain(|run| {
+            let terminal = run.conclusion_is_terminal();
+            if !terminal {
+                log!(
+                    Severity::Warn,
+                    "Run {} is still {}; skipping it without --include-active.",
+                    run.id,
+                    paint_status(args, run.state().unwrap_or("active"))
+                );
+            }
+            terminal
+        });
+    }
+
+    // Leave workflows that are running alone this cycle, so dashboards stay consistent
+    if args.skip_if_workflow_running && !runs.is_empty() {
+        let running = workflows_with_active_runs(repo)?;
+        let mut skipped: BTreeMap<&str, usize> = BTreeMap::new();
+        for run in &runs {
+            if let Some(name) = run.name.as_deref().filter(|name| running.contains(*name)) {
+                *skipped.entry(name).or_default() += 1;
+            }
+        }
+        for (name, count) in &skipped {
+            say!(
+                "{} Workflow {} has an active run; skipping its {} run(s) this cycle.",
+                "⏸️".yellow(),
+                name.yellow().bold(),
+                count
+            );
+        }
+        runs.retain(|run| run.name.as_ref().is_none_or(|name| !running.contains(name)));
+    }
+
+    // Protected workflows are a hard guard, applied after every other filter
+    let protected = remove_protected_runs(&mut runs, &args.protect_workflow);
+    if protected > 0 {
+        say!(
+            "{} Kept {} run(s) of protected workflows",
+            "🛡️".green(),
+            protected.to_string().green().bold()
+        );
+    }
+    if !args.no_sort {
+        order_runs(&mut runs, args.delete_order, args.seed);
+    }
+
+    // A preview deletes nothing, so it has no survivors to vouch for
+    if let Some(note) = args.annotate_surviving_runs.as_deref()
+        && !args.dry_run
+    {
+        let selected: HashSet<i64> = runs.iter().map(|run| run.id).collect();
+        let mut annotated = ANNOTATED_RUNS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for &id in fetched.iter().filter(|id| !selected.contains(id)) {
+            if let Entry::Vacant(entry) = annotated.entry(id) {
+                entry.insert(note.to_string());
+                annotate_run(id, note, repo);
+            }
+        }
+    }
+    Ok(runs)
+}
+
+/// Fetch the runs (matching the statuses) of merged or closed pull requests
+fn select_pr_runs(statuses: &[String], repo: Option<&str>) -> Result<Vec<WorkflowRun>> {
+    let closed = fetch_pull_requests("closed", repo)?;
+    let open = fetch_pull_requests("open", repo)?;
+    let default_branch = fetch_default_branch(repo)?;
+
+    let mut runs = Vec::new();
+    for pr in pr_branches_to_purge(&closed, &open, &default_branch) {
+        let pr_runs: Vec<WorkflowRun> = fetch_pr_runs(pr, repo)?
+            .into_iter()
+            .filter(|run| run_matches_statuses(run, statuses))
+            .collect();
+        if !pr_runs.is_empty() {
+            say!(
+                "{} PR #{} ({}): {} run(s)",
+                "🔀".cyan(),
+                pr.number,
+                pr.head_branch.cyan().bold(),
+                pr_runs.len().to_string().cyan().bold()
+            );
+        }
+        runs.extend(pr_runs);
+    }
+
+    let mut seen = HashSet::new();
+    runs.retain(|run| seen.insert(run.id));
+    Ok(runs)
+}
+
+/// Fetch the runs (matching the statuses) of one pull request
+fn select_single_pr_runs(
+    pr: &PullRequestRef,
+    statuses: &[String],
+    repo: Option<&str>,
+) -> Result<Vec<WorkflowRun>> {
+    let runs: Vec<WorkflowRun> = fetch_pr_runs(pr, repo)?
+        .into_iter()
+        .filter(|run| run_matches_statuses(run, statuses))
+        .collect();
+    say!(
+        "{} PR #{} ({}): {} run(s)",
+        "🔀".cyan(),
+        pr.number,
+        pr.head_branch.cyan().bold(),
+        runs.len().to_string().cyan().bold()
+    );
+    Ok(runs)
+}
+
+/// Fetch the runs (matching the statuses) on the branches of closed pull requests
+fn select_pr_cleanup_runs(
+    args: &Args,
+    statuses: &[String],
+    repo: Option<&str>,
+) -> Result<Vec<WorkflowRun>> {
+    let closed_before = args
+        .pr_closed_days
+        .map(|days| chrono::Utc::now() - chrono::Duration::days(i64::from(days)));
+    let branches = fetch_closed_pr_branches(repo, closed_before)?;
+    let open = fetch_pull_requests("open", repo)?;
+    let default_branch = fetch_default_branch(repo)?;
+    let busy: HashSet<&str> = open.iter().map(|pr| pr.head_branch.as_str()).collect();
+
+    let mut runs = Vec::new();
+    for branch in branches
+        .iter()
+        .filter(|branch| **branch != default_branch && !busy.contains(branch.as_str()))
+    {
+        let branch_runs = fetch_branch_runs(branch, statuses, repo)?;
+        if !branch_runs.is_empty() {
+            say!(
+                "{} Branch {}: {} run(s)",
+                "🔀".cyan(),
+                branch.cyan().bold(),
+                branch_runs.len().to_string().cyan().bold()
+            );
+        }
+        runs.extend(branch_runs);
+    }
+
+    let mut seen = HashSet::new();
+    runs.retain(|run| seen.insert(run.id));
+    Ok(runs)
+}
+
+/// Fetch the runs whose workflow was deleted or disabled, reporting them per workflow
+fn select_orphaned_runs(statuses: &[String], repo: Option<&str>) -> Result<Vec<WorkflowRun>> {
+    let orphaned = fetch_orphaned_runs(&fetch_runs_resumable(statuses, repo)?, repo)?;
+    for (workflow_id, runs) in group_runs_by_workflow(&orphaned) {
+        let name = runs
+            .first()
+            .and_then(|run| run.name.as_deref())
+            .unwrap_or("unknown");
+        say!(
+            "{} Orphaned workflow {} ({}): {} run(s)",
+            "🗂️".cyan(),
+            name.cyan().bold(),
+            workflow_id,
+            runs.len().to_string().cyan().bold()
+        );
+    }
+
+    Ok(orphaned)
+}
+
+/// Log that a run was reviewed and kept; [`record_retained`] writes the lasting record
+fn annotate_run(run_id: i64, note: &str, repo: Option<&str>) {
+    say!(
+        "{} Run {}{} kept: {}",
+        "📝".cyan(),
+        run_id,
+        repo.map(|repo| format!(" ({})", repo)).unwrap_or_default(),
+        note
+    );
+}
+
+/// Delete a single GitHub Action run, giving up after `timeout`
+fn delete_run(run_id: i64, repo: Option<&str>, timeout: Duration) -> Result<()> {
+    let output = timed_command(
+        gh_command(repo).args(["run", "delete", &run_id.to_string()]),
+        timeout,
+    )
+    .with_context(|| format!("gh run delete failed for run {}", run_id))?;
+
+    if !output.status.success() {
+        return Err(anyhow::Error::new(GhExitError::from_output(&output))
+            .context(format!("Delete failed for run {}", run_id)));
+    }
+
+    Ok(())
+}
+
+/// Extra per-run work around each deletion: hooks and log archiving
+struct DeleteSteps<'a> {
+    pre: Option<&'a str>,
+    post: Option<&'a str>,
+    /// Serializes hook execution across the deletion threads
+    serial: Mutex<()>,
+    logs: Option<LogArchive<'a>>,
+    /// Paces deletions under --runs-per-minute
+    pace: Option<Mutex<TokenBucket>>,
+    /// Caps concurrent deletions per workflow under --per-workflow-concurrency
+    workflow_slots: Option<GroupedConcurrencyPool>,
+    delete_timeout: Duration,
+    /// Under --strict, the status filter each run must still match at delete time
+    verify: Option<&'a [String]>,
+    /// Log each failed deletion (--verbose)
+    verbose: bool,
+}
+
+/// Where --tail-logs saves run logs, and how much room is left
+struct LogArchive<'a> {
+    dir: &'a Path,
+    max_size: u64,
+    dir_max_size: Option<u64>,
+    /// Bytes currently in the log directory
+    used: AtomicU64,
+}
+
+impl LogArchive<'_> {
+    /// Whether the log directory has reached --log-dir-max-size
+    fn is_full(&self) -> bool {
+        self.dir_max_size
+            .is_some_and(|max| self.used.load(Ordering::Relaxed) >= max)
+    }
+
+    /// Save the log of a run, refusing once the directory is full
+    fn save(&self, run_id: i64, repo: Option<&str>) -> Result<()> {
+        if self.is_full() {
+            anyhow::bail!(
+                "Log directory size cap reached; not deleting run {}",
+                run_id
+            );
+        }
+
+        let path = save_run_log(run_id, self.dir, repo, self.max_size)?;
+        let size = fs::metadata(&path)?.len();
+        self.used.fetch_add(size, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl<'a> DeleteSteps<'a> {
+    fn new(args: &'a Args, statuses: &'a [String]) -> Result<Self> {
+        let logs = if args.tail_logs {
+            fs::create_dir_all(&args.log_output_dir)
+                .with_context(|| format!("Failed to create {}", args.log_output_dir.display()))?;
+            Some(LogArchive {
+                dir: &args.log_output_dir,
+                max_size: args.log_max_size,
+                dir_max_size: args.log_dir_max_size,
+                used: AtomicU64::new(dir_size(&args.log_output_dir)?),
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            pre: args.pre_delete_hook.as_deref(),
+            post: args.post_delete_hook.as_deref(),
+            serial: Mutex::new(()),
+            logs,
+            pace: args
+                .runs_per_minute
+                .map(|n| Mutex::new(TokenBucket::per_minute(n))),
+            workflow_slots: args
+                .per_workflow_concurrency
+                .map(GroupedConcurrencyPool::new),
+            delete_timeout: Duration::from_secs(args.delete_timeout),
+            // Cancelled runs no longer match the runtime statuses they were selected by
+            verify: (args.strict && !args.cancel_then_delete).then_some(statuses),
+            verbose: args.verbose,
+        })
+    }
+
+    /// Run one hook for `run_id`, returning whether it succeeded
+    fn run(&self, command: &str, run_id: i64, repo: Option<&str>) -> Result<bool> {
+        let _guard = self.serial.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let run_id = run_id.to_string();
+        let mut env = vec![("GH_RUN_ID", run_id.as_str())];
+        if let Some(repo) = repo {
+            env.push(("GH_REPO", repo));
+        }
+        run_hook(command, &env)
+    }
+}
+
+/// Delete a single run, running the hooks and log archiving around it
+///
+/// Returns whether the run was deleted (a pre-delete hook may decline it).
+fn delete_run_with_steps(run_id: i64, repo: Option<&str>, steps: &DeleteSteps) -> Result<bool> {
+    if let Some(statuses) = steps.verify {
+        let current = fetch_run_details(run_id, repo)?;
+        if !run_matches_statuses(&current, statuses) {
+            say!(
+                "{} Run {} is now {}, which no longer matches the filter; skipping.",
+                "⏭️".yellow(),
+                run_id,
+                current.state().unwrap_or("unknown")
+            );
+            return Ok(false);
+        }
+    }
+
+    if let Some(pre) = steps.pre
+        && !steps.run(pre, run_id, repo)?
+    {
+        say!(
+            "{} Pre-delete hook declined run {}, skipping.",
+            "⏭️".yellow(),
+            run_id
+        );
+        return Ok(false);
+    }
+
+    if let Some(logs) = &steps.logs {
+        logs.save(run_id, repo)?;
+    }
+
+    if let Some(pace) = &steps.pace {
+        pace.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .wait_until_available(1);
+    }
+    delete_run(run_id, repo, steps.delete_timeout)?;
+    DELETED_RUNS.fetch_add(1, Ordering::Relaxed);
+    if CHATTER_TO_STDERR.load(Ordering::Relaxed) {
+        println!("{}", run_id);
+    }
+
+    if let Some(post) = steps.post {
+        match steps.run(post, run_id, repo) {
+            Ok(true) => {},
+            Ok(false) => log!(Severity::Warn, "Post-delete hook failed for run {}", run_id),
+            Err(e) => log!(Severity::Warn, "{}", e),
+        }
+    }
+
+    Ok(true)
+}
+
+/// Printed by --explain-cleanup
+const CLEANUP_EXPLANATION: &str = "\
+Deleting a workflow run removes:
+  - the run and its jobs, as listed in the Actions tab
+  - the logs of every job step
+  - the artifacts uploaded by the run
+
+It does not remove:
+  - check runs and their annotations on the commit: the Checks API has no delete endpoint,
+    so they can stay visible on the commit and in pull request checks
+  - commit statuses reported by the run
+  - Actions caches (use the purge-caches subcommand)
+  - deployments and environment history created by the run
+";
+
+/// Deletion tasks queued per worker thread at most
+const IN_FLIGHT_PER_JOB: usize = 4;
+
+/// What happened to one batch of deletions
+struct BatchReport {
+    /// Runs actually deleted (per status label) and failed deletions
+    stats: Stats,
+    deleted_ids: Vec<i64>,
+    failed_ids: Vec<i64>,
+    /// Failed runs whose gh exit code says retrying cannot help (usage or auth errors)
+    unretryable_ids: Vec<i64>,
+    hit_secondary_limit: bool,
+    /// Some deletion got a 403 that is not a rate limit
+    permission_denied: bool,
+}
+
+/// Delete runs, each labelled with its status, with `jobs` worker threads and check for
+/// secondary rate limit errors
+///
+/// Deletions wait on the network, so a fixed pool fed through a channel (at most
+/// IN_FLIGHT_PER_JOB queued runs per worker) keeps exactly `jobs` requests going. Under
+/// --per-workflow-concurrency, each deletion also holds a slot of its run's group in
+/// `workflows` (runs missing from it share one group).
+fn delete_runs_parallel(
+    runs: &[(i64, &str)],
+    workflows: &HashMap<i64, String>,
+    repo: Option<&str>,
+    steps: &DeleteSteps,
+    jobs: usize,
+) -> Result<BatchReport> {
+    let results = run_in_worker_pool(runs, jobs, jobs * IN_FLIGHT_PER_JOB, |&(id, label)| {
+        let _slot = steps
+            .workflow_slots
+            .as_ref()
+            .map(|slots| slots.acquire(workflows.get(&id).map_or("unknown", String::as_str)));
+        let result = delete_run_with_steps(id, repo, steps);
+        if result.is_err() {
+            FAILED_DELETES.fetch_add(1, Ordering::Relaxed);
+        }
+        (id, label, result)
+    });
+
+    let mut stats = Stats::default();
+    let mut deleted_ids = Vec::new();
+    let mut failed_ids = Vec::new();
+    let mut unretryable_ids = Vec::new();
+    let mut permission_denied = false;
+    let mut errors = Vec::new();
+    for (id, label, result) in results {
+        match result {
+            Ok(true) => {
+                stats.record_deleted(label);
+                deleted_ids.push(id);
+            },
+            Ok(false) => {},
+            Err(e) => {
+                let exit = gh_exit_error(&e);
+                if steps.verbose {
+                    let code = exit
+                        .and_then(|exit| exit.code)
+                        .map_or_else(|| "-".to_string(), |code| code.to_string());
+                    say!("   {} [gh exit code {}] {:#}", "❌".red(), code, e);
+                }
+                if exit.is_some_and(|exit| !exit.is_retryable()) {
+                    unretryable_ids.push(id);
+                }
+                permission_denied |= exit.is_some_and(GhExitError::is_permission_denied);
+                failed_ids.push(id);
+                errors.push(e);
+            },
+        }
+    }
+
+    stats.errors = errors.len();
+    Ok(BatchReport {
+        stats,
+        deleted_ids,
+        failed_ids,
+        unretryable_ids,
+        // Check if any error mentions secondary rate limit
+        hit_secondary_limit: check_for_secondary_rate_limit(&errors),
+        permission_denied,
+    })
+}
+
+/// Whether progress output (stdout, or stderr under CHATTER_TO_STDERR) goes to a terminal
+fn progress_is_terminal() -> bool {
+    if CHATTER_TO_STDERR.load(Ordering::Relaxed) {
+        io::stderr().is_terminal()
+    } else {
+        io::stdout().is_terminal()
+    }
+}
+
+/// The --stats-interval cadence, unless the progress output is not a terminal
+fn stats_interval(args: &Args) -> Option<Duration> {
+    args.stats_interval
+        .filter(|_| progress_is_terminal())
+        .map(Duration::from_secs)
+}
+
+/// Whether to print a progress bar per status under the overall one
+///
+/// Only for several statuses on a terminal; JSON output keeps to the single bar.
+fn per_status_progress_bars(args: &Args, statuses: &[String]) -> bool {
+    statuses.len() > 1 && progress_is_terminal() && args.format != OutputFormat::Json
+}
+
+/// Run `work` (a batch of `batch_size` deletions), printing its progress every `interval`
+fn with_stats_ticker<T>(
+    interval: Option<Duration>,
+    batch_size: usize,
+    work: impl FnOnce() -> T,
+) -> T {
+    let Some(interval) = interval else {
+        return work();
+    };
+
+    let deleted_before = DELETED_RUNS.load(Ordering::Relaxed);
+    let failed_before = FAILED_DELETES.load(Ordering::Relaxed);
+    let started = Instant::now();
+    // Dropping the sender wakes the ticker up immediately once the batch is done
+    let (stop, stopped) = mpsc::channel::<()>();
+
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                let deleted = DELETED_RUNS.load(Ordering::Relaxed) - deleted_before;
+                let failed = FAILED_DELETES.load(Ordering::Relaxed) - failed_before;
+                say!(
+                    "{} {}/{} deleted, {} failures, {:.1}/sec",
+                    "📈".cyan(),
+                    deleted.to_string().cyan().bold(),
+                    batch_size,
+                    failed,
+                    deleted as f64 / started.elapsed().as_secs_f64()
+                );
+            }
+        });
+
+        let result = work();
+        drop(stop);
+        result
+    })
+}
+
+/// Re-check the quota after a batch and log how much it actually consumed
+fn log_batch_quota_delta(before: &RateLimitCore, batch_size: usize) {
+    let Ok(after) = check_rate_limit() else {
+        return;
+    };
+
+    match quota_consumed(before, &after) {
+        Some(consumed) => say!(
+            "{} Batch quota delta: {} -> {} ({} API calls for {} runs)",
+            "📉".cyan(),
+            before.remaining,
+            after.remaining,
+            consumed.to_string().cyan().bold(),
+            batch_size
+        ),
+        None => say!(
+            "{} Batch quota delta: rate limit window reset during the batch ({} left)",
+            "📉".cyan(),
+            after.remaining
+        ),
+    }
+}
+
+/// Cancel runs in parallel and wait for them to stop, returning the ones that did
+fn cancel_runs_parallel(run_ids: &[i64], repo: Option<&str>, timeout: Duration) -> Vec<i64> {
+    run_ids
+        .par_iter()
+        .filter_map(|&id| {
+            match cancel_run(id, repo).and_then(|_| wait_for_cancellation(id, repo, timeout)) {
+                Ok(()) => Some(id),
+                Err(e) => {
+                    log!(Severity::Warn, "{}", e);
+                    None
+                },
+            }
+        })
+        .collect()
+}
+
+/// Fetch the full records of all runs matching the statuses, sorted by ID
+///
+/// Pages through the runs, starting at --start-cursor the first time, and prints the cursor to
+/// resume from after each page.
+fn fetch_runs_resumable(statuses: &[String], repo: Option<&str>) -> Result<Vec<WorkflowRun>> {
+    let mut pagination = PAGINATION.lock().unwrap_or_else(PoisonError::into_inner);
+    let runs = fetch_runs_paginated(statuses, repo, &mut pagination, |state| {
+        if let Some(cursor) = &state.cursor {
+            eprintln!("{} Resume cursor: --start-cursor {}", "🧭".cyan(), cursor);
+        }
+    })?;
+    for status in &pagination.capped {
+        let message = format!(
+            "Stopped listing {} runs after {} page(s) (--max-list-pages); more remain. Narrow \
+             the filters to reach them.",
+            status,
+            pagination.max_pages.unwrap_or_default()
+        );
+        eprintln!("{}", styled(Severity::Warn, &message));
+    }
+    Ok(runs)
+}
+
+/// Print the matching runs as a table (`list` subcommand)
+fn list_runs(args: &Args, statuses: &[String], repo: Option<&str>) -> Result<()> {
+    let mut runs = fetch_runs_resumable(statuses, repo)?;
+    if !args.no_sort {
+        runs.sort_unstable_by_key(|run| run.id);
+    }
+    if let Some(n) = args.sample {
+        say!(
+            "{} Showing {} of {} runs",
+            "🎲".cyan(),
+            n.min(runs.len()),
+            runs.len()
+        );
+        print_sample(args, &runs, n);
+        return Ok(());
+    }
+    if args.no_dedup {
+        let now = chrono::Utc::now();
+        let rows: Vec<MatchedRunTableRow> = runs_by_matched_status(&runs, statuses)
+            .into_iter()
+            .map(|(matched, run)| MatchedRunTableRow {
+                matched: matched.to_string(),
+                run: RunTableRow::from_run(run, now),
+            })
+            .collect();
+        let duplicates = rows.len().saturating_sub(runs.len());
+        println!("{}", render_table(&rows, args.table_style));
+        say!(
+            "{} {} row(s) repeat a run that matched more than one status",
+            "🔁".cyan(),
+            duplicates
+        );
+        return Ok(());
+    }
+    let rows: Vec<RunTableRow> = runs.into_iter().map(RunTableRow::from).collect();
+
+    println!("{}", render_table(&rows, args.table_style));
+    Ok(())
+}
+
+/// Print every workflow of the repository as a table (`workflows` subcommand)
+fn list_workflows(args: &Args, repo: Option<&str>) -> Result<()> {
+    let mut workflows = fetch_workflows(repo)?;
+    workflows.sort_by(|a, b| a.name.cmp(&b.name));
+    let rows: Vec<WorkflowTableRow> = workflows.into_iter().map(WorkflowTableRow::from).collect();
+
+    println!("{}", render_table(&rows, args.table_style));
+    Ok(())
+}
+
+/// Print the number of matching runs per status as a table (`stats` subcommand)
+fn show_stats(args: &Args, statuses: &[String], repo: Option<&str>) -> Result<()> {
+    let rows = status_count_rows(statuses, repo)?;
+    println!("{}", render_table(&rows, args.table_style));
+    Ok(())
+}
+
+/// The number of runs of each status, one count-only API call per status
+fn status_count_rows(statuses: &[String], repo: Option<&str>) -> Result<Vec<StatusCountRow>> {
+    statuses
+        .iter()
+        .map(|status| {
+            Ok(StatusCountRow {
+                status: status.clone(),
+                runs: fetch_runs_count_only(status, repo)?,
+            })
+        })
+        .collect()
+}
+
+/// Check gh, the quota and run listing, failing if any check does (`test-connection` subcommand)
+fn test_connection(args: &Args, repo: Option<&str>) -> Result<()> {
+    let completed = ["completed".to_string()];
+    let results = [
+        ConnectionTestResult::from_result("gh CLI", validate_gh_cli()),
+        ConnectionTestResult::from_result(
+            "Rate limit",
+            check_rate_limit().map(|rl| format!("{} requests left", rl.remaining)),
+        ),
+        ConnectionTestResult::from_result(
+            "List runs",
+            fetch_runs_with_statuses_limit(&completed, repo, 1, true)
+                .map(|runs| format!("can list runs ({} sampled)", runs.len())),
+        ),
+    ];
+    print_test_results(&results, args.table_style);
+
+    let failed = results.iter().filter(|result| !result.passed).count();
+    if failed > 0 {
+        anyhow::bail!("{} of {} connection checks failed", failed, results.len());
+    }
+    Ok(())
+}
+
+/// Install the environment of every spawned gh: the GitHub Actions credentials and host, then
+/// the --http-proxy variables
+fn install_command_env(args: &Args, actions: Option<&GithubActionsContext>) -> Result<()> {
+    let mut env = CommandEnv::default();
+    if let Some(context) = actions {
+        env = env.with_github_actions(context);
+    }
+    if let Some(proxy) = &args.http_proxy {
+        validate_proxy_url(proxy)?;
+        env = env.with_proxy(proxy, args.no_proxy.as_deref());
+    }
+    if env.vars().is_empty() {
+        return Ok(());
+    }
+    env.install()
+}
+
+/// Make sure gh actually reaches GitHub through the proxy installed by [`install_command_env`]
+fn configure_proxy(proxy: &str) -> Result<()> {
+    check_rate_limit()
+        .with_context(|| format!("Proxy '{}' is unreachable or cannot reach GitHub", proxy))?;
+
+    say!("{} Using proxy {}", "🌐".cyan(), proxy.cyan().bold());
+    Ok(())
+}
+
+/// Print how many runs match, optionally per workflow (`count` subcommand)
+fn count_runs(
+    args: &Args,
+    statuses: &[String],
+    repo: Option<&str>,
+    by_workflow: bool,
+) -> Result<()> {
+    // The totals suffice unless the runs themselves are needed or could be counted twice
+    if !by_workflow
+        && !args.histogram
+        && args.start_cursor.is_none()
+        && redundant_statuses(statuses).is_empty()
+    {
+        let total = statuses
+            .iter()
+            .map(|status| fetch_runs_count_only(status, repo))
+            .sum::<Result<usize>>()?;
+        say!(
+            "{} {} runs match status: {}",
+            "🧮".cyan(),
+            total.to_string().cyan().bold(),
+            statuses.join(", ").cyan()
+        );
+        return Ok(());
+    }
+
+    let runs = fetch_runs_resumable(statuses, repo)?;
+    say!(
+        "{} {} runs match status: {}",
+        "🧮".cyan(),
+        runs.len().to_string().cyan().bold(),
+        statuses.join(", ").cyan()
+    );
+
+    if by_workflow {
+        println!(
+            "{}",
+            render_table(&count_runs_by_workflow(&runs), args.table_style)
+        );
+    }
+    if args.histogram {
+        print_histogram(args, &runs);
+    }
+    Ok(())
+}
+
+/// Print the --histogram table of run ages
+fn print_histogram(args: &Args, runs: &[WorkflowRun]) {
+    println!(
+        "{}",
+        render_table(&age_histogram(runs, chrono::Utc::now()), args.table_style)
+    );
+}
+
+/// Print the runs a purge would delete, as text or as a JSON report
+fn print_dry_run(
+    args: &Args,
+    runs: &[WorkflowRun],
+    statuses: &[String],
+    repo: Option<&str>,
+) -> Result<()> {
+    if args.format == OutputFormat::Json {
+        let report = DryRunReport::new(runs, repo, statuses);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).context("Failed to serialize dry run report")?
+        );
+        return Ok(());
+    }
+
+    if let Some(n) = args.sample {
+        say!(
+            "{} Dry run: would delete {} runs; a sample of {}:",
+            "🔍".cyan(),
+            runs.len().to_string().cyan().bold(),
+            n.min(runs.len())
+        );
+        print_sample(args, runs, n);
+        if args.histogram {
+            print_histogram(args, runs);
+        }
+        return Ok(());
+    }
+
+    say!(
+        "{} Dry run: would delete {} runs:",
+        "🔍".cyan(),
+        runs.len().to_string().cyan().bold()
+    );
+    if args.no_dedup {
+        for (matched, run) in runs_by_matched_status(runs, statuses) {
+            say!(
+                "  {} {} ({}) matched {}",
+                run.id,
+                run.name.as_deref().unwrap_or("-"),
+                paint_status(args, run.state().unwrap_or("-")),
+                matched
+            );
+        }
+    } else {
+        for run in runs {
+            say!(
+                "  {} {} ({})",
+                run.id,
+                run.name.as_deref().unwrap_or("-"),
+                paint_status(args, run.state().unwrap_or("-"))
+            );
+        }
+    }
+    if args.histogram {
+        print_histogram(args, runs);
+    }
+    Ok(())
+}
+
+/// Print the --sample table: `n` runs spread evenly over `runs`
+fn print_sample(args: &Args, runs: &[WorkflowRun], n: usize) {
+    let now = chrono::Utc::now();
+    let rows: Vec<RunTableRow> = sample_evenly(runs, n)
+        .into_iter()
+        .map(|run| RunTableRow::from_run(run, now))
+        .collect();
+    println!("{}", render_table(&rows, args.table_style));
+}
+
+/// A status label, coloured by status under --colorize-status
+fn paint_status(args: &Args, status: &str) -> ColoredString {
+    let colorize = args
+        .colorize_status
+        .unwrap_or_else(|| colored::control::SHOULD_COLORIZE.should_colorize());
+    match status_color(status).filter(|_| colorize) {
+        Some(color) => status.color(color),
+        None => status.normal(),
+    }
+}
+
+/// The gh commands a purge with these settings would run, in order (`--explain`)
+///
+/// Commands repeated per status are listed once per status; commands repeated per run, page or
+/// workflow use placeholders.
+fn explain_commands(args: &Args, statuses: &[String]) -> Result<Vec<String>> {
+    if args.command.is_some() {
+        anyhow::bail!("--explain describes purges; it cannot be combined with a subcommand");
+    }
+
+    let mut commands = Vec::new();
+    let repo = match &args.repo_pattern {
+        Some(pattern) => {
+            let (org, _) = parse_repo_pattern(pattern)?;
+            let endpoint = format!("orgs/{}/repos?per_page=100", org);
+            commands.push(format_gh_command(None, &[
+                "api",
+                &endpoint,
+                "--paginate",
+                "--jq",
+                ".[] | {full_name, visibility}",
+            ]));
+            // Everything below then runs for each matching repository
+            Some("<REPO>")
+        },
+        None => args.repo.as_deref(),
+    };
+    if args.check_permissions && !args.dry_run {
+        commands.push(format_gh_command(repo, &[
+            "api",
+            "repos/{owner}/{repo}",
+            "--jq",
+            ".permissions",
+        ]));
+    }
+    if args.status_counts_before_delete {
+        for status in statuses {
+            let endpoint = format!(
+                "repos/{{owner}}/{{repo}}/actions/runs?status={}&per_page=1",
+                status
+            );
+            commands.push(format_gh_command(repo, &[
+                "api",
+                &endpoint,
+                "--jq",
+                ".total_count",
+            ]));
+        }
+    }
+    commands.push(format_gh_command(None, &[
+        "api",
+        "rate_limit",
+        "--jq",
+        ".resources.core",
+    ]));
+    let mut gh = |gh_args: &[&str]| commands.push(format_gh_command(repo, gh_args));
+
+    // Selection
+    if args.orphaned_workflows {
+        for status in statuses {
+            let endpoint = format!(
+                "repos/{{owner}}/{{repo}}/actions/runs?status={}&per_page={}&page=<PAGE>",
+                status, RUNS_PER_PAGE
+            );
+            gh(&["api", &endpoint, "--jq", ".workflow_runs[]"]);
+        }
+        gh(&[
+            "api",
+            "repos/{owner}/{repo}/actions/workflows",
+            "--paginate",
+            "--jq",
+            ".workflows[] | select(.state == \"active\") | .id",
+        ]);
+    } else if args.resolve_pr_runs {
+        let limit = PR_LIST_LIMIT.to_string();
+        for state in ["closed", "open"] {
+            gh(&[
+                "pr",
+                "list",
+                "--state",
+                state,
+                "--limit",
+                &limit,
+                "--json",
+                "number,headRefName",
+            ]);
+        }
+        gh(&[
+            "repo",
+            "view",
+            "--json",
+            "defaultBranchRef",
+            "--jq",
+            ".defaultBranchRef.name",
+        ]);
+        gh(&[
+            "run",
+            "list",
+            "--event",
+            "pull_request",
+            "--branch",
+            "<BRANCH>",
+            "--limit",
+            &RUN_LIST_LIMIT.to_string(),
+            "--json",
+            RUN_LIST_JSON_FIELDS,
+        ]);
+    } else if let Some(number) = args.pr {
+        gh(&[
+            "pr",
+            "view",
+            &number.to_string(),
+            "--json",
+            "number,headRefName",
+        ]);
+        gh(&[
+            "run",
+            "list",
+            "--event",
+            "pull_request",
+            "--branch",
+            "<BRANCH>",
+            "--limit",
+            &RUN_LIST_LIMIT.to_string(),
+            "--json",
+            RUN_LIST_JSON_FIELDS,
+        ]);
+    } else if args.pr_cleanup {
+        let limit = PR_LIST_LIMIT.to_string();
+        gh(&[
+            "pr",
+            "list",
+            "--state",
+            "closed",
+            "--limit",
+            &limit,
+            "--json",
+            "headRefName,closedAt",
+        ]);
+        gh(&[
+            "pr",
+            "list",
+            "--state",
+            "open",
+            "--limit",
+            &limit,
+            "--json",
+            "number,headRefName",
+        ]);
+        gh(&[
+            "repo",
+            "view",
+            "--json",
+            "defaultBranchRef",
+            "--jq",
+            ".defaultBranchRef.name",
+        ]);
+        let limit = RUN_LIST_LIMIT.to_string();
+        gh(&[
+            "run",
+            "list",
+            "--branch",
+            "<BRANCH>",
+            "--limit",
+            &limit,
+            "--json",
+            RUN_LIST_JSON_FIELDS,
+        ]);
+    } else {
+        let limit = if args.adaptive_limit {
+            "<LIMIT>".to_string()
+        } else {
+            RUN_LIST_LIMIT.to_string()
+        };
+        for status in statuses {
+            gh(&[
+                "run",
+                "list",
+                "--status",
+                status,
+                "--limit",
+                &limit,
+                "--json",
+                RUN_LIST_JSON_FIELDS,
+            ]);
+        }
+    }
+    if args.workflow_path_glob.is_some() {
+        gh(&[
+            "api",
+            "repos/{owner}/{repo}/actions/workflows",
+            "--paginate",
+            "--jq",
+            ".workflows[]",
+        ]);
+    }
+    if args.delete_runs_matching_jq.is_some() {
+        gh(&["api", "repos/{owner}/{repo}/actions/runs/<RUN_ID>"]);
+    }
+    if args.delete_runs_without_jobs {
+        gh(&[
+            "api",
+            "repos/{owner}/{repo}/actions/runs/<RUN_ID>/jobs",
+            "--jq",
+            ".total_count",
+        ]);
+    }
+    if args.skip_if_workflow_running {
+        let limit = RUN_LIST_LIMIT.to_string();
+        for status in ["in_progress", "queued"] {
+            gh(&[
+                "run",
+                "list",
+                "--status",
+                status,
+                "--limit",
+                &limit,
+                "--json",
+                RUN_LIST_JSON_FIELDS,
+            ]);
+        }
+    }
+    if args.dry_run {
+        return Ok(commands);
+    }
+
+    // Per run
+    if args.cancel_then_delete {
+        gh(&["run", "cancel", "<RUN_ID>"]);
+        gh(&[
+            "run", "view", "<RUN_ID>", "--json", "status", "-q", ".status",
+        ]);
+    }
+    if args.strict && !args.cancel_then_delete {
+        gh(&["api", "repos/{owner}/{repo}/actions/runs/<RUN_ID>"]);
+    }
+    if args.tail_logs {
+        gh(&["run", "view", "<RUN_ID>", "--log"]);
+    }
+    gh(&["run", "delete", "<RUN_ID>"]);
+
+    if args.delete_empty_workflows {
+        gh(&[
+            "api",
+            "repos/{owner}/{repo}/actions/workflows",
+            "--paginate",
+            "--jq",
+            ".workflows[] | select(.state == \"active\")",
+        ]);
+        gh(&[
+            "api",
+            "repos/{owner}/{repo}/actions/workflows/<WORKFLOW_ID>/runs?per_page=1",
+            "--jq",
+            ".total_count",
+        ]);
+        gh(&["workflow", "disable", "<WORKFLOW_ID>"]);
+    }
+    Ok(commands)
+}
+
+/// Resolve the repositories to purge; `None` means the current directory's repository
+fn resolve_repos(args: &Args) -> Result<Vec<Option<String>>> {
+    let Some(pattern) = &args.repo_pattern else {
+        return Ok(vec![args.repo.clone()]);
+    };
+
+    // Neither flag given means both visibilities are included
+    let include_private = args.include_private || !args.include_public;
+    let include_public = args.include_public || !args.include_private;
+
+    let repos = expand_repo_pattern(pattern, include_private, include_public)
+        .context("Failed to expand --repo-pattern")?;
+    if repos.is_empty() {
+        anyhow::bail!("No repositories match pattern '{}'", pattern);
+    }
+
+    say!(
+        "{} Matched {} repositories for {}",
+        "🔎".cyan(),
+        repos.len().to_string().cyan().bold(),
+        pattern.cyan().bold()
+    );
+
+    Ok(repos.into_iter().map(|r| Some(r.full_name)).collect())
 }
 
-/// Delete a single GitHub Action run
-fn delete_run(run_id: i64) -> Result<()> {
-    let output = Command::new("gh")
-        .args(["run", "delete", &run_id.to_string()])
-        .output()
-        .context("Failed to execute gh run delete")?;
+/// Block until the repository has no active runs, honouring --wait-timeout-action
+fn wait_for_idle(args: &Args, repo: Option<&str>) -> Result<()> {
+    say!(
+        "{} Waiting up to {}s for queued/in-progress runs to finish...",
+        "⏳".yellow(),
+        args.wait_timeout.to_string().yellow().bold()
+    );
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Delete failed for run {}: {}", run_id, stderr);
+    let options = IdlePollOptions {
+        repo: repo.map(str::to_string),
+        interval: Duration::from_secs(15),
+    };
+    match poll_until_idle(&options, Duration::from_secs(args.wait_timeout))? {
+        IdleResult::Idle => {
+            say!("{} No active runs left.", "✅".cyan());
+        },
+        IdleResult::TimedOut {
+            active,
+        } => match args.wait_timeout_action {
+            WaitTimeoutAction::Abort => anyhow::bail!(
+                "{} run(s) still active after waiting {}s",
+                active,
+                args.wait_timeout
+            ),
+            WaitTimeoutAction::Proceed => log!(
+                Severity::Warn,
+                "{} run(s) still active after {}s; proceeding anyway.",
+                active,
+                args.wait_timeout
+            ),
+        },
     }
 
     Ok(())
 }
 
-/// Delete runs in parallel and check for secondary rate limit errors
-fn delete_runs_parallel(run_ids: &[i64]) -> Result<bool> {
-    // Use a thread-safe container to collect errors
-    let errors: Vec<_> = run_ids
-        .par_iter()
-        .map(|&id| delete_run(id))
-        .filter_map(|result| result.err())
-        .collect();
+/// Print a question where the chatter goes and read one answer line, `None` on EOF
+fn prompt(question: &str) -> Result<Option<String>> {
+    if CHATTER_TO_STDERR.load(Ordering::Relaxed) {
+        eprint!("{}", question);
+        io::stderr().flush().context("Failed to flush stderr")?;
+    } else {
+        print!("{}", question);
+        io::stdout().flush().context("Failed to flush stdout")?;
+    }
 
-    // Check if any error mentions secondary rate limit
-    Ok(check_for_secondary_rate_limit(&errors))
+    let mut input = String::new();
+    if io::stdin()
+        .read_line(&mut input)
+        .context("Failed to read from stdin")?
+        == 0
+    {
+        return Ok(None);
+    }
+    Ok(Some(input))
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+/// Ask the user what to do with a fetched batch, re-prompting on invalid input
+///
+/// `status_counts` (the --status-counts-before-delete table) is shown above the summary.
+fn prompt_batch_decision(run_ids: &[i64], status_counts: Option<&str>) -> Result<BatchDecision> {
+    if let Some(table) = status_counts {
+        say!("{} Matching runs per status:\n{}", "🧮".cyan(), table);
+    }
+    say!("{} {}", "🔎".cyan(), deletion_summary(run_ids));
+    loop {
+        let question = format!(
+            "{} Delete {} runs? [p]roceed / [s]kip / [a]bort: ",
+            "❓".yellow(),
+            run_ids.len().to_string().yellow().bold()
+        );
+        // EOF: nobody is there to answer, so stop safely
+        let Some(input) = prompt(&question)? else {
+            return Ok(BatchDecision::Abort);
+        };
 
-    // Parse and validate the status filter
-    let statuses = parse_and_validate_statuses(&args.status).context("Invalid status argument")?;
+        if let Some(decision) = parse_batch_decision(&input) {
+            return Ok(decision);
+        }
+    }
+}
 
-    println!(
-        "{}",
-        "🚀 GitHub Run Purge - Rust Edition".bright_cyan().bold()
+/// Report the active workflows left without runs and offer to disable them
+///
+/// Disabling needs an explicit "yes"; with --dry-run the workflows are only listed.
+fn tidy_empty_workflows(args: &Args, repo: Option<&str>) -> Result<()> {
+    let mut empty = Vec::new();
+    for workflow in fetch_active_workflows(repo)? {
+        if fetch_workflow_run_count(workflow.id, repo)? == 0 {
+            empty.push(workflow);
+        }
+    }
+
+    if empty.is_empty() {
+        say!("{} Every active workflow still has runs.", "✅".cyan());
+        return Ok(());
+    }
+
+    say!(
+        "{} {} active workflows have no runs left:",
+        "🗂️".cyan(),
+        empty.len().to_string().cyan().bold()
     );
-    println!(
-        "{} Filtering by status: {}",
-        "🎯".cyan(),
-        statuses.join(", ").cyan().bold()
+    for workflow in &empty {
+        say!("  {} {} ({})", workflow.id, workflow.name, workflow.path);
+    }
+
+    if args.dry_run {
+        return Ok(());
+    }
+
+    let question = format!(
+        "{} Disable these {} workflows? They will stop triggering. [y/N]: ",
+        "❓".yellow(),
+        empty.len().to_string().yellow().bold()
     );
-    println!();
+    if !prompt(&question)?.is_some_and(|answer| is_affirmative(&answer)) {
+        say!("{} Leaving the workflows enabled.", "⏭️".cyan());
+        return Ok(());
+    }
+
+    for workflow in &empty {
+        match disable_workflow(workflow.id, repo) {
+            Ok(()) => say!("{} Disabled {}", "🔕".cyan(), workflow.name),
+            Err(e) => log!(Severity::Warn, "{}", e),
+        }
+    }
+    Ok(())
+}
 
+/// Wait until GitHub is reachable and the quota is healthy, hibernating while it is low
+///
+/// Network failures are retried indefinitely; gives up after [`MAX_MALFORMED_RATE_LIMIT_RETRIES`]
+/// consecutive unusable responses from gh.
+fn await_quota(args: &Args) -> Result<RateLimitCore> {
+    let mut malformed = 0;
     loop {
-        // --- 1. PRE-FLIGHT QUOTA CHECK 🛡️ ---
         let rate_limit = match check_rate_limit() {
             Ok(rl) => rl,
-            Err(e) => {
-                println!(
-                    "{} Cannot reach GitHub API: {}",
-                    "❌".red(),
-                    e.to_string().red()
+            // Retrying cannot fix output gh keeps mangling, so only give it a few chances
+            Err(e) if is_malformed_rate_limit(&e) => {
+                malformed += 1;
+                if malformed > MAX_MALFORMED_RATE_LIMIT_RETRIES {
+                    return Err(e.context(format!(
+                        "gh api rate_limit returned unusable output {} times in a row",
+                        malformed
+                    )));
+                }
+                log!(
+                    Severity::Warn,
+                    "Unusable rate limit response ({}); retrying ({}/{})...",
+                    e,
+                    malformed,
+                    MAX_MALFORMED_RATE_LIMIT_RETRIES
                 );
-                println!("{} Checking network/lockout...", "⏳".yellow());
+                thread::sleep(Duration::from_secs(5));
+                continue;
+            },
+            Err(e) => {
+                malformed = 0;
+                log!(Severity::Warn, "Cannot reach GitHub API: {}", e);
+                say!("{} Checking network/lockout...", "⏳".yellow());
                 thread::sleep(Duration::from_secs(30));
                 continue;
             },
         };
 
+        // Below the reserve the purge stops instead of waiting
+        if args
+            .min_remaining_after
+            .is_some_and(|reserve| i64::from(rate_limit.remaining) < i64::from(reserve))
+        {
+            return Ok(rate_limit);
+        }
+
         // If credits are low, enter hibernation mode 😴
-        if should_hibernate(rate_limit.remaining, 50) {
+        if should_hibernate(rate_limit.remaining, HIBERNATE_AT.load(Ordering::Relaxed)) {
             let current_time = chrono::Utc::now().timestamp();
-            let wait_seconds = calculate_wait_seconds(rate_limit.reset, current_time);
-            let wait_minutes = wait_seconds / 60;
 
-            println!(
+            say!(
                 "{} API QUOTA EXHAUSTED ({} left).",
                 "🚫".red(),
                 rate_limit.remaining.to_string().red().bold()
             );
-            println!(
-                "{} Hibernating for {} minute(s) until reset...",
+
+            let wait_seconds = match reset_wait_seconds(rate_limit.reset, current_time) {
+                Some(wait_seconds) => wait_seconds,
+                None => {
+                    log!(
+                        Severity::Warn,
+                        "Nonsensical rate limit reset ({}); waiting {}s instead.",
+                        rate_limit.reset,
+                        FALLBACK_WAIT_SECONDS
+                    );
+                    FALLBACK_WAIT_SECONDS
+                },
+            };
+            let min_wait = i64::try_from(args.clock_skew_wait).unwrap_or(i64::MAX);
+            let wait_seconds = match clock_skew_wait(wait_seconds, min_wait) {
+                Some(skew_wait) => {
+                    log!(
+                        Severity::Warn,
+                        "The rate limit reset ({}) already passed but the quota is exhausted; the \
+                         local clock may be ahead of GitHub's. Waiting {}s.",
+                        format_reset_time(rate_limit.reset, args.utc),
+                        skew_wait
+                    );
+                    skew_wait
+                },
+                None => wait_seconds,
+            };
+            let wait_minutes = wait_seconds / 60;
+
+            let nap =
+                capped_hibernation_duration(wait_seconds, Duration::from_secs(args.hibernate_cap));
+            say!(
+                "{} Hibernating until {} (in {}m; re-checking in {}s)...",
                 "⏳".yellow(),
-                wait_minutes.to_string().yellow().bold()
+                format_reset_time(current_time + wait_seconds, args.utc)
+                    .yellow()
+                    .bold(),
+                wait_minutes,
+                nap.as_secs()
             );
 
-            thread::sleep(Duration::from_secs((wait_seconds + 10) as u64));
+            write_status(args, ToolPhase::Hibernating);
+            thread::sleep(nap);
             continue;
         }
 
+        return Ok(rate_limit);
+    }
+}
+
+/// Add freshly deleted runs to the report and checkpoint it to --report, if any
+///
+/// Like the status file, a checkpoint that cannot be written only produces a warning.
+fn record_deleted(args: &Args, report: &mut PurgeReport, run_ids: Vec<i64>) {
+    report.deleted.extend(run_ids);
+    save_report(args, report);
+}
+
+/// Copy the runs annotated by --annotate-surviving-runs into the report, saving it if any are new
+fn record_retained(args: &Args, report: &mut PurgeReport) {
+    let annotated = ANNOTATED_RUNS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if annotated.len() == report.retained.len() {
+        return;
+    }
+    report
+        .retained
+        .extend(annotated.iter().map(|(&id, note)| (id, note.clone())));
+    drop(annotated);
+    save_report(args, report);
+}
+
+/// Write the --report checkpoint, if one was asked for
+fn save_report(args: &Args, report: &PurgeReport) {
+    let Some(path) = &args.report else {
+        return;
+    };
+    if let Err(e) = report.save(path) {
+        log!(Severity::Warn, "{:#}", e);
+    }
+}
+
+/// Start from the --resume-from-report checkpoint, if any, warning when it was made for another
+/// repository or status filter
+fn initial_report(
+    args: &Args,
+    repos: &[Option<String>],
+    statuses: &[String],
+) -> Result<PurgeReport> {
+    let repos: Vec<String> = repos.iter().flatten().cloned().collect();
+    let Some(path) = &args.resume_from_report else {
+        return Ok(PurgeReport::new(repos, statuses.to_vec()));
+    };
+
+    let prior = PurgeReport::load(path)?;
+    if !prior.matches(&repos, statuses) {
+        log!(
+            Severity::Warn,
+            "{} was written for repos [{}] and status [{}]; make sure it belongs to this purge.",
+            path.display(),
+            prior.repos.join(", "),
+            prior.statuses.join(", ")
+        );
+    }
+    say!(
+        "{} Resuming: {} runs already deleted according to {}",
+        "⏯️".cyan(),
+        prior.deleted.len().to_string().cyan().bold(),
+        path.display()
+    );
+
+    // Runs given up on before get a fresh set of attempts
+    Ok(PurgeReport {
+        repos,
+        statuses: statuses.to_vec(),
+        deleted: prior.deleted,
+        skipped: BTreeSet::new(),
+        retained: prior.retained,
+    })
+}
+
+/// Record what the tool is doing in the --status-file and the health endpoint, if any
+///
+/// A status file that cannot be written only produces a warning: it must not stop a purge.
+fn write_status(args: &Args, phase: ToolPhase) {
+    if let Some(health) = HEALTH.get() {
+        health.set_phase(phase);
+    }
+    let Some(path) = &args.status_file else {
+        return;
+    };
+    let state = ToolState::new(phase, DELETED_RUNS.load(Ordering::Relaxed));
+    if let Err(e) = state.write(path, args.status_file_format) {
+        log!(Severity::Warn, "{:#}", e);
+    }
+}
+
+/// Runs to list per status: what the quota can delete under --adaptive-limit, else the default
+fn run_list_limit(args: &Args, rate_limit: &RateLimitCore) -> usize {
+    if !args.adaptive_limit {
+        return RUN_LIST_LIMIT;
+    }
+    // gh rejects --limit 0; await_quota only returns with the quota at the threshold or above
+    compute_safe_batch_size(
+        rate_limit.remaining,
+        HIBERNATE_AT.load(Ordering::Relaxed),
+        1,
+    )
+    .clamp(1, RUN_LIST_LIMIT)
+}
+
+/// API calls left under --max-total-api-calls, `None` without a budget
+fn api_budget_left(args: &Args) -> Option<usize> {
+    args.max_total_api_calls
+        .map(|max| max.saturating_sub(api_calls_used()))
+}
+
+/// API calls left above --min-remaining-after, `None` without a reserve
+///
+/// `checked_at` is the [`api_calls_used`] count when `rate_limit` was fetched.
+fn reserve_left(args: &Args, rate_limit: &RateLimitCore, checked_at: usize) -> Option<usize> {
+    args.min_remaining_after.map(|reserve| {
+        calls_above_reserve(
+            rate_limit.remaining,
+            api_calls_used().saturating_sub(checked_at),
+            reserve,
+        )
+    })
+}
+
+/// Caches deleted between two quota checks
+const CACHE_BATCH_SIZE: usize = 100;
+
+/// Delete the caches matching the filters (`purge-caches` subcommand)
+fn purge_caches(
+    args: &Args,
+    repo: Option<&str>,
+    key_pattern: Option<&str>,
+    older_than_days: Option<u32>,
+    git_ref: Option<&str>,
+) -> Result<PurgeOutcome> {
+    let rate_limit = await_quota(args)?;
+    say!(
+        "{} Quota healthy ({} left). Fetching caches...",
+        "⚖️".cyan(),
+        rate_limit.remaining.to_string().cyan().bold()
+    );
+
+    let older_than =
+        older_than_days.map(|days| chrono::Utc::now() - chrono::Duration::days(i64::from(days)));
+    let caches = filter_caches(&list_caches(repo, key_pattern)?, older_than, git_ref);
+
+    if caches.is_empty() {
+        say!("{} Success: No caches match the filters!", "✨".green());
+        return Ok(PurgeOutcome::Completed);
+    }
+
+    let total_mib = caches.iter().map(|cache| cache.size_in_bytes).sum::<u64>() as f64 / 1048576.0;
+    if args.dry_run {
+        say!(
+            "{} Dry run: would delete {} caches ({:.1} MiB):",
+            "🔍".cyan(),
+            caches.len().to_string().cyan().bold(),
+            total_mib
+        );
+        for cache in &caches {
+            say!("  {} {} ({})", cache.id, cache.key, cache.git_ref);
+        }
+        return Ok(PurgeOutcome::Completed);
+    }
+
+    // Configure rayon to use at most --jobs threads for this operation
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(args.jobs)
+        .build()
+        .context("Failed to create thread pool")?;
+
+    let mut attempted = 0;
+    let mut failed = 0;
+    let mut outcome = PurgeOutcome::Completed;
+    for batch in caches.chunks(CACHE_BATCH_SIZE) {
+        let mut batch = batch;
+        if let Some(left) = api_budget_left(args)
+            && left < batch.len()
+        {
+            batch = &batch[..left];
+            outcome = PurgeOutcome::BudgetExhausted;
+        }
+        let rate_limit = await_quota(args)?;
+        if let Some(left) = reserve_left(args, &rate_limit, api_calls_used())
+            && left < batch.len()
+        {
+            batch = &batch[..left];
+            outcome = PurgeOutcome::ReserveReached;
+        }
+        if batch.is_empty() {
+            break;
+        }
+        say!(
+            "{} Deleting {} caches in parallel...",
+            "🔨".blue(),
+            batch.len().to_string().blue().bold()
+        );
+
+        let errors: Vec<_> = pool.install(|| {
+            batch
+                .par_iter()
+                .map(|cache| delete_cache(repo, cache.id))
+                .filter_map(|result| result.err())
+                .collect()
+        });
+        for e in &errors {
+            log!(Severity::Warn, "{}", e);
+        }
+        attempted += batch.len();
+        failed += errors.len();
+
+        if check_for_secondary_rate_limit(&errors) {
+            say!(
+                "{} Secondary rate limit hit (moving too fast!).",
+                "🐢".red()
+            );
+            say!("{} Taking a 60s nap to appease GitHub...", "⏳".yellow());
+            thread::sleep(Duration::from_secs(60));
+        }
+    }
+
+    say!(
+        "{} Deleted {} of {} caches ({:.1} MiB matched).",
+        "✨".green(),
+        (attempted - failed).to_string().green().bold(),
+        caches.len(),
+        total_mib
+    );
+    Ok(outcome)
+}
+
+/// Purge matching runs of a single repository until none are left
+fn purge_repo(
+    args: &Args,
+    statuses: &[String],
+    repo: Option<&str>,
+    pr: Option<&PullRequestRef>,
+    report: &mut PurgeReport,
+) -> Result<(PurgeOutcome, Stats)> {
+    if args.wait_for_in_progress {
+        wait_for_idle(args, repo)?;
+    }
+
+    let steps = DeleteSteps::new(args, statuses)?;
+    let mut progress = PurgeProgress::default();
+    let mut status_progress = StatusProgress::default();
+    let per_status_bars = per_status_progress_bars(args, statuses);
+    let mut stats = Stats::default();
+    let started = Instant::now();
+    let push_gateway = args
+        .prometheus_push_gateway
+        .as_deref()
+        .map(PushGatewayClient::new);
+    let mut empty_fetches = 0;
+    let mut attempts = HashMap::new();
+    // Runs of batches skipped at the --prompt-per-batch prompt
+    let mut declined: HashSet<i64> = HashSet::new();
+    // Shown once: with the first prompt under --prompt-per-batch, else right away
+    let mut status_counts = if args.status_counts_before_delete {
+        Some(render_table(
+            &status_count_rows(statuses, repo)?,
+            args.table_style,
+        ))
+    } else {
+        None
+    };
+    if !args.prompt_per_batch
+        && let Some(table) = status_counts.take()
+    {
+        say!("{} Matching runs per status:\n{}", "🧮".cyan(), table);
+    }
+
+    let outcome = loop {
+        if api_budget_left(args) == Some(0) {
+            let left = progress.total() - progress.processed();
+            if left > 0 {
+                say!(
+                    "{} {} fetched run(s) left undeleted",
+                    "📋".yellow(),
+                    left.to_string().yellow().bold()
+                );
+            }
+            break PurgeOutcome::BudgetExhausted;
+        }
+
+        // --- 1. PRE-FLIGHT QUOTA CHECK 🛡️ ---
+        let rate_limit = await_quota(args)?;
+        let checked_at = api_calls_used();
+        if reserve_left(args, &rate_limit, checked_at) == Some(0) {
+            break PurgeOutcome::ReserveReached;
+        }
+
         // --- 2. FETCH RUNS 🔍 ---
-        println!(
+        write_status(args, ToolPhase::Fetching);
+        say!(
             "{} Quota healthy ({} left). Fetching runs...",
             "⚖️".cyan(),
             rate_limit.remaining.to_string().cyan().bold()
         );
 
-        let run_ids = match fetch_runs_with_statuses(&statuses) {
-            Ok(runs) => runs,
+        let limit = run_list_limit(args, &rate_limit);
+        let selected = select_runs(args, statuses, repo, pr, &report.deleted, limit);
+        record_retained(args, report);
+        let runs = match selected {
+            Ok(mut runs) => {
+                runs.retain(|run| !report.skipped.contains(&run.id) && !declined.contains(&run.id));
+                runs
+            },
             Err(e) => {
-                println!(
-                    "{} Error fetching runs: {}",
-                    "⚠️".red(),
-                    e.to_string().red()
-                );
+                log!(Severity::Warn, "Error fetching runs: {}", e);
                 thread::sleep(Duration::from_secs(5));
                 continue;
             },
         };
 
+        // Each run keeps its status label through deletion for the per-status summary
+        let mut batch: Vec<(i64, &str)> = runs
+            .iter()
+            .map(|run| (run.id, run.state().unwrap_or("unknown")))
+            .collect();
+        // Groups for --per-workflow-concurrency, falling back to the workflow ID
+        let workflows: HashMap<i64, String> = runs
+            .iter()
+            .map(|run| {
+                let name = run.name.clone();
+                (run.id, name.unwrap_or_else(|| run.workflow_id.to_string()))
+            })
+            .collect();
+
         // Check if we're done
-        if run_ids.is_empty() {
-            println!(
+        if batch.is_empty() {
+            empty_fetches += 1;
+            if empty_fetches < args.confirm_empty {
+                say!(
+                    "{} No runs found; confirming in {}s ({}/{})...",
+                    "🔁".cyan(),
+                    CONFIRM_EMPTY_DELAY.as_secs(),
+                    empty_fetches,
+                    args.confirm_empty
+                );
+                thread::sleep(CONFIRM_EMPTY_DELAY);
+                continue;
+            }
+            say!(
                 "{} Success: No more runs found with status: {}!",
                 "✨".green(),
                 statuses.join(", ").green().bold()
             );
-            break;
+            break PurgeOutcome::Completed;
+        }
+
+        empty_fetches = 0;
+        FOUND_RUNS.store(true, Ordering::Relaxed);
+
+        if args.dry_run {
+            print_dry_run(args, &runs, statuses, repo)?;
+            break PurgeOutcome::Completed;
+        }
+
+        let run_ids: Vec<i64> = batch.iter().map(|&(id, _)| id).collect();
+        if args.prompt_per_batch {
+            match prompt_batch_decision(&run_ids, status_counts.take().as_deref())? {
+                BatchDecision::Proceed => {},
+                BatchDecision::Skip => {
+                    say!(
+                        "{} Batch skipped; its runs are left alone. Fetching again...",
+                        "⏭️".cyan()
+                    );
+                    declined.extend(run_ids);
+                    continue;
+                },
+                BatchDecision::Abort => break PurgeOutcome::Aborted,
+            }
+        }
+
+        progress.discover(&run_ids);
+        status_progress.discover(&batch);
+
+        // Each deletion costs at least one call: never start more than the budget allows
+        if let Some(left) = api_budget_left(args) {
+            batch.truncate(left);
+        }
+        match reserve_left(args, &rate_limit, checked_at) {
+            Some(0) => break PurgeOutcome::ReserveReached,
+            Some(left) => batch.truncate(left),
+            None => {},
+        }
+
+        if args.cancel_then_delete {
+            // Configure rayon to use at most --jobs threads for the cancellations
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(args.jobs)
+                .build()
+                .context("Failed to create thread pool")?;
+            say!(
+                "{} Cancelling {} active runs first...",
+                "🛑".yellow(),
+                batch.len().to_string().yellow().bold()
+            );
+            let timeout = Duration::from_secs(args.cancel_timeout);
+            let run_ids: Vec<i64> = batch.iter().map(|&(id, _)| id).collect();
+            let stopped: HashSet<i64> = pool
+                .install(|| cancel_runs_parallel(&run_ids, repo, timeout))
+                .into_iter()
+                .collect();
+            batch.retain(|(id, _)| stopped.contains(id));
         }
+        let run_ids: Vec<i64> = batch.iter().map(|&(id, _)| id).collect();
 
         // --- 3. DELETE RUNS 🚀 ---
-        println!(
+        write_status(args, ToolPhase::Deleting);
+        say!(
             "{} Deleting {} runs in parallel...",
             "🔨".blue(),
             run_ids.len().to_string().blue().bold()
         );
+        if args.verbose {
+            say!(
+                "   {}",
+                truncated_id_list(&run_ids, args.truncate_run_id_output).dimmed()
+            );
+        }
+
+        let batch_report = with_stats_ticker(stats_interval(args), batch.len(), || {
+            delete_runs_parallel(&batch, &workflows, repo, &steps, args.jobs)
+        })?;
+        // The permissions check only sees the user's role; a batch refused outright means the
+        // token itself cannot delete runs
+        if args.check_permissions
+            && batch_report.permission_denied
+            && batch_report.deleted_ids.is_empty()
+        {
+            log!(
+                Severity::Error,
+                "GitHub refused to delete runs in {} (HTTP 403): the token lacks actions:write",
+                repo.unwrap_or("the current repository")
+            );
+            std::process::exit(PERMISSION_EXIT_CODE);
+        }
+        stats.merge(batch_report.stats);
+        let mut given_up: BTreeSet<i64> = record_failed_attempts(
+            &mut attempts,
+            &batch_report.failed_ids,
+            args.max_retries_per_run,
+        )
+        .into_iter()
+        .collect();
+        // Our own bad invocation or a missing login fails the same way every time
+        given_up.extend(&batch_report.unretryable_ids);
+        if !given_up.is_empty() {
+            let ids: Vec<i64> = given_up.iter().copied().collect();
+            log!(
+                Severity::Warn,
+                "Giving up on {} run(s) after {} failed deletions or a usage/auth error: {}",
+                ids.len(),
+                args.max_retries_per_run,
+                truncated_id_list(&ids, args.truncate_run_id_output)
+            );
+            report.skipped.extend(given_up);
+        }
+        record_deleted(args, report, batch_report.deleted_ids);
+        if let Some(gateway) = &push_gateway {
+            let metrics = prometheus_metrics(&stats, api_calls_used());
+            let instance = repo.unwrap_or("current");
+            if let Err(e) = gateway.push(&metrics, &args.prometheus_job_name, instance) {
+                log!(Severity::Warn, "{}", e);
+            }
+        }
 
-        // Configure rayon to use max 15 threads for this operation
-        let pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(15)
-            .build()
-            .context("Failed to create thread pool")?;
+        progress.mark_processed(&run_ids);
+        status_progress.mark_processed(&batch);
+        say!("{} Progress: {}", "📊".cyan(), progress.render(30));
+        if per_status_bars {
+            for line in status_progress.render(30) {
+                say!("   {}", line);
+            }
+        }
+        if args.compact {
+            print_compact(&stats.compact_line(repo, started.elapsed()), false);
+        }
 
-        let hit_secondary_limit = pool.install(|| delete_runs_parallel(&run_ids))?;
+        log_batch_quota_delta(&rate_limit, run_ids.len());
+
+        if steps.logs.as_ref().is_some_and(LogArchive::is_full) {
+            anyhow::bail!(
+                "Log directory {} reached --log-dir-max-size; stopping before deleting runs without \
+                 their logs",
+                args.log_output_dir.display()
+            );
+        }
 
-        if hit_secondary_limit {
-            println!(
+        if batch_report.hit_secondary_limit {
+            say!(
                 "{} Secondary rate limit hit (moving too fast!).",
                 "🐢".red()
             );
-            println!("{} Taking a 60s nap to appease GitHub...", "⏳".yellow());
+            say!("{} Taking a 60s nap to appease GitHub...", "⏳".yellow());
             thread::sleep(Duration::from_secs(60));
             continue;
         }
 
         // Short breather to stay under the radar 🌬️
-        println!("{} Batch cleared. Polling for more...", "✅".cyan());
+        say!("{} Batch cleared. Polling for more...", "✅".cyan());
         thread::sleep(Duration::from_secs(2));
+    };
+
+    if outcome == PurgeOutcome::Completed && !args.dry_run && !args.no_final_check {
+        let checked = final_check(args, statuses, repo, pr, &report.deleted);
+        record_retained(args, report);
+        checked?;
+    }
+
+    if !stats.per_status.is_empty() {
+        say!(
+            "{} Deleted {}",
+            "🧾".cyan(),
+            format_status_counts(&stats.per_status).cyan().bold()
+        );
+    }
+    if args.compact {
+        print_compact(&stats.compact_line(repo, started.elapsed()), true);
+    }
+    Ok((outcome, stats))
+}
+
+/// Re-fetch the eligible runs once more and warn if any are left after a completed purge
+///
+/// Runs recorded as deleted are fetched too: finding one means a deletion did not stick.
+fn final_check(
+    args: &Args,
+    statuses: &[String],
+    repo: Option<&str>,
+    pr: Option<&PullRequestRef>,
+    deleted: &BTreeSet<i64>,
+) -> Result<()> {
+    let remaining = select_runs(args, statuses, repo, pr, &BTreeSet::new(), RUN_LIST_LIMIT)?;
+    if remaining.is_empty() {
+        say!("{} Final check: no eligible runs left.", "✔️".green());
+        return Ok(());
+    }
+
+    let ids: Vec<i64> = remaining.iter().map(|run| run.id).collect();
+    let resurrected = ids.iter().filter(|id| deleted.contains(id)).count();
+    // Loud on purpose: not even --compact hides this
+    eprintln!(
+        "{} Final check: {} eligible run(s) still exist ({} of them reported as deleted): {}",
+        "🚨".red(),
+        ids.len().to_string().red().bold(),
+        resurrected,
+        truncated_id_list(&ids, args.truncate_run_id_output).red()
+    );
+    eprintln!(
+        "   Deletions may be failing silently (e.g. missing permissions); re-run with --verbose."
+    );
+    Ok(())
+}
+
+/// Print a --compact line; unfinished lines are only shown, and redrawn in place, on a terminal
+fn print_compact(line: &str, done: bool) {
+    let mut out: Box<dyn Write> = if CHATTER_TO_STDERR.load(Ordering::Relaxed) {
+        Box::new(io::stderr())
+    } else {
+        Box::new(io::stdout())
+    };
+    let tty = progress_is_terminal();
+
+    // Write errors on the terminal are not worth aborting a purge for
+    let _ = match (tty, done) {
+        (true, false) => write!(out, "\r{}\x1b[K", line),
+        (true, true) => writeln!(out, "\r{}\x1b[K", line),
+        (false, false) => Ok(()),
+        (false, true) => writeln!(out, "{}", line),
+    };
+    let _ = out.flush();
+}
+
+/// Parse a percentage in (0, 100]
+fn parse_percent(value: &str) -> Result<f64, String> {
+    let percent: f64 = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+    if percent > 0.0 && percent <= 100.0 {
+        Ok(percent)
+    } else {
+        Err(format!("{} must be above 0 and at most 100", percent))
+    }
+}
+
+fn main() {
+    if let Err(e) = run() {
+        log!(Severity::Error, "{:#}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    let mut args = Args::parse();
+    args.resolve_presets();
+    set_warning_handler(|message| log!(Severity::Warn, "{}", message));
+    if args.explain_cleanup {
+        print!("{}", CLEANUP_EXPLANATION);
+        return Ok(());
+    }
+    let actions = if args.github_actions_context || GithubActionsContext::detected() {
+        let context = GithubActionsContext::from_env();
+        if context.is_none() && args.github_actions_context {
+            anyhow::bail!("--github-actions-context needs GITHUB_REPOSITORY to be set");
+        }
+        context
+    } else {
+        None
+    };
+    if let Some(context) = &actions
+        && args.repo.is_none()
+        && args.repo_pattern.is_none()
+    {
+        args.repo = Some(context.repository.clone());
+    }
+    install_command_env(&args, actions.as_ref())?;
+    CHATTER_TO_STDERR.store(
+        args.output_ids_only
+            || args.format == OutputFormat::Json
+            || args.export_config.is_some()
+            || args.explain
+            || matches!(
+                args.summary_format,
+                Some(SummaryFormat::Json | SummaryFormat::Toml)
+            ),
+        Ordering::Relaxed,
+    );
+    // The subcommands' output is the point of running them, so --compact only quiets purges
+    COMPACT.store(
+        args.compact && args.command.is_none() && args.export_config.is_none(),
+        Ordering::Relaxed,
+    );
+
+    // Parse and validate the status filter
+    let status = if args.cancel_then_delete {
+        "in_progress,queued"
+    } else {
+        &args.status
+    };
+    let statuses =
+        parse_statuses(status, args.skip_status_validation).context("Invalid status argument")?;
+    if let Some(pattern) = args.workflow_path_glob.as_deref() {
+        filter_by_workflow_path(&[], pattern).context("Invalid --workflow-path-glob")?;
+    }
+    if let Some(pattern) = args.run_name_filter.as_deref() {
+        filter_by_run_name(&[], pattern).context("Invalid --run-name-filter")?;
+    }
+    if let Some(pattern) = args.include_run_ids_matching.as_deref() {
+        filter_ids_by_regex(&[], pattern).context("Invalid --include-run-ids-matching")?;
+    }
+    if let Some(pattern) = args.exclude_run_ids_matching.as_deref() {
+        filter_ids_by_regex(&[], pattern).context("Invalid --exclude-run-ids-matching")?;
+    }
+    if let Some(expr) = args.delete_runs_matching_jq.as_deref() {
+        filter_runs_with_jq("", expr).context("Invalid --delete-runs-matching-jq")?;
+    }
+    let jobs = effective_jobs(args.jobs, args.force_high_concurrency);
+    if jobs < args.jobs {
+        log!(
+            Severity::Warn,
+            "--jobs {} exceeds the ceiling of {}; using {} (pass --force-high-concurrency to go \
+             above).",
+            args.jobs,
+            MAX_JOBS,
+            jobs
+        );
+        args.jobs = jobs;
+    }
+
+    if let Some(format) = args.export_config {
+        args.status = statuses.join(",");
+        let config = serde_json::to_value(&args).context("Failed to serialize settings")?;
+        match format {
+            ConfigFormat::Toml => print!("{}", json_to_toml(&config)?),
+            ConfigFormat::Json => println!("{}", serde_json::to_string_pretty(&config)?),
+        }
+        return Ok(());
+    }
+    if let Some(secs) = args.timeout {
+        HardTimeout::spawn(secs);
+    }
+    if let Some(remaining) = args.simulate_rate_limit {
+        simulate_rate_limit(remaining);
+    }
+    if args.explain {
+        for command in explain_commands(&args, &statuses)? {
+            println!("{}", command);
+        }
+        return Ok(());
+    }
+    if let Some(port) = args.health_check_port {
+        let health = HealthServer::new(args.health_check_bind, port);
+        let addr = health.spawn()?;
+        say!("{} Health check listening on {}/health", "🩺".cyan(), addr);
+        let _ = HEALTH.set(health);
+    }
+    write_status(&args, ToolPhase::Starting);
+    let mut pagination = PaginationState::new(args.start_cursor.clone());
+    pagination.max_pages = args.max_list_pages;
+    pagination.position().context("Invalid --start-cursor")?;
+    *PAGINATION.lock().unwrap_or_else(PoisonError::into_inner) = pagination;
+
+    say!(
+        "{}",
+        "🚀 GitHub Run Purge - Rust Edition".bright_cyan().bold()
+    );
+    say!(
+        "{} Filtering by status: {}",
+        "🎯".cyan(),
+        statuses.join(", ").cyan().bold()
+    );
+    // test-connection reports an unusable gh in its results instead
+    if args.command != Some(Commands::TestConnection) {
+        match detect_gh_version()? {
+            Some(version) => check_gh_compatibility(version)?,
+            None => log!(
+                Severity::Warn,
+                "Could not determine the gh version; assuming it is recent enough."
+            ),
+        }
+    }
+    // Deleting active runs is only expected from --cancel-then-delete; listing them is harmless
+    let runtime = runtime_statuses_in(&statuses);
+    if args.command.is_none()
+        && !args.cancel_then_delete
+        && !args.include_active
+        && !runtime.is_empty()
+    {
+        if args.strict {
+            anyhow::bail!(
+                "Runtime statuses ({}) select active runs, and deleting them aborts them; use \
+                 --cancel-then-delete, --include-active or conclusion statuses",
+                runtime.join(", ")
+            );
+        }
+        log!(
+            Severity::Warn,
+            "{} select active runs, which are skipped unless --include-active is set. Pass \
+             --strict to refuse this filter.",
+            runtime.join(", ")
+        );
+    }
+    let redundant = redundant_statuses(&statuses);
+    if !redundant.is_empty() {
+        if args.strict {
+            anyhow::bail!(
+                "Statuses {} are already covered by 'completed'; drop them or 'completed'",
+                redundant.join(", ")
+            );
+        }
+        log!(
+            Severity::Info,
+            "'completed' already covers {}; listing them too only costs extra API calls.",
+            redundant.join(", ")
+        );
+    }
+    if args.skip_status_validation {
+        log!(
+            Severity::Warn,
+            "--skip-status-validation in use; unknown statuses may cause gh run list to fail"
+        );
+    }
+    say!();
+
+    if args.jitter_start > 0 {
+        let jitter = random_jitter_seconds(args.jitter_start);
+        say!(
+            "{} Jitter start: sleeping {}s before the first API call...",
+            "🎲".cyan(),
+            jitter.to_string().cyan().bold()
+        );
+        thread::sleep(Duration::from_secs(jitter));
+    }
+
+    if let Some(proxy) = &args.http_proxy {
+        configure_proxy(proxy)?;
+    }
+    if let Some(percent) = args.auto_threshold {
+        let rate_limit =
+            check_rate_limit().context("Failed to read the rate limit for --auto-threshold")?;
+        match rate_limit.limit {
+            Some(limit) => {
+                let threshold = compute_auto_threshold(limit, percent);
+                HIBERNATE_AT.store(threshold, Ordering::Relaxed);
+                say!(
+                    "{} Hibernating below {} remaining requests ({}% of {})",
+                    "📐".cyan(),
+                    threshold.to_string().cyan().bold(),
+                    percent,
+                    limit
+                );
+            },
+            None => log!(
+                Severity::Warn,
+                "The rate limit reports no quota; keeping the default threshold of {}.",
+                HIBERNATE_THRESHOLD
+            ),
+        }
+    }
+    if let Some(required) = args.require_quota {
+        let rate_limit =
+            check_rate_limit().context("Failed to read the rate limit for --require-quota")?;
+        if should_hibernate(rate_limit.remaining, required) {
+            log!(
+                Severity::Error,
+                "Only {} requests remain, --require-quota needs {}; not starting.",
+                rate_limit.remaining,
+                required
+            );
+            std::process::exit(QUOTA_EXIT_CODE);
+        }
+    }
+
+    let repos = resolve_repos(&args)?;
+    // Only runs and caches are deleted; a dry run needs no more than read access
+    let deletes = matches!(args.command, None | Some(Commands::PurgeCaches { .. }));
+    if args.check_permissions && deletes && !args.dry_run {
+        for repo in &repos {
+            match check_actions_write_permission(repo.as_deref()) {
+                Ok(ActionsWriteAccess::Granted) => {},
+                Ok(ActionsWriteAccess::Unknown) => log!(
+                    Severity::Warn,
+                    "Cannot tell whether the token may delete runs in {} (no permissions \
+                     reported, as for installation tokens); a refused first deletion will stop \
+                     the purge.",
+                    repo.as_deref().unwrap_or("the current repository")
+                ),
+                Err(e) => {
+                    log!(Severity::Error, "{:#}", e);
+                    std::process::exit(PERMISSION_EXIT_CODE);
+                },
+            }
+        }
+    }
+    // An unknown --pr must stop the purge here, not be retried as a failed fetch
+    let pull_requests = repos
+        .iter()
+        .map(|repo| match args.pr {
+            Some(number) if args.command.is_none() => {
+                fetch_pull_request(number, repo.as_deref()).map(Some)
+            },
+            _ => Ok(None),
+        })
+        .collect::<Result<Vec<_>>>()
+        .context("Invalid --pr")?;
+    let mut report = initial_report(&args, &repos, &statuses)?;
+    // Runs a resumed report already vouches for are not annotated again
+    ANNOTATED_RUNS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .extend(report.retained.clone());
+    let mut totals = Stats::default();
+    let mut per_repo: Vec<(String, Stats)> = Vec::new();
+    let mut all_completed = true;
+    for (index, repo) in repos.iter().enumerate() {
+        if let Some(repo) = repo {
+            say!("{} Repository: {}", "📦".cyan(), repo.cyan().bold());
+        }
+        let outcome = match &args.command {
+            Some(Commands::List) => {
+                list_runs(&args, &statuses, repo.as_deref())?;
+                PurgeOutcome::Completed
+            },
+            Some(Commands::Stats) => {
+                show_stats(&args, &statuses, repo.as_deref())?;
+                PurgeOutcome::Completed
+            },
+            Some(Commands::Workflows) => {
+                list_workflows(&args, repo.as_deref())?;
+                PurgeOutcome::Completed
+            },
+            Some(Commands::TestConnection) => {
+                test_connection(&args, repo.as_deref())?;
+                PurgeOutcome::Completed
+            },
+            Some(Commands::Count {
+                count_by_workflow,
+            }) => {
+                count_runs(&args, &statuses, repo.as_deref(), *count_by_workflow)?;
+                PurgeOutcome::Completed
+            },
+            Some(Commands::PurgeCaches {
+                cache_key_pattern,
+                cache_older_than_days,
+                cache_ref,
+            }) => purge_caches(
+                &args,
+                repo.as_deref(),
+                cache_key_pattern.as_deref(),
+                *cache_older_than_days,
+                cache_ref.as_deref(),
+            )?,
+            None => {
+                let (outcome, stats) = purge_repo(
+                    &args,
+                    &statuses,
+                    repo.as_deref(),
+                    pull_requests[index].as_ref(),
+                    &mut report,
+                )?;
+                if let Some(repo) = repo {
+                    per_repo.push((repo.clone(), stats.clone()));
+                }
+                totals.merge(stats);
+                if outcome == PurgeOutcome::Completed && args.delete_empty_workflows {
+                    tidy_empty_workflows(&args, repo.as_deref())?;
+                }
+                outcome
+            },
+        };
+
+        all_completed &= outcome == PurgeOutcome::Completed;
+        match outcome {
+            PurgeOutcome::Completed => {},
+            PurgeOutcome::Aborted => {
+                say!("{} Aborted by user.", "🛑".yellow());
+                break;
+            },
+            PurgeOutcome::BudgetExhausted => {
+                say!(
+                    "{} API call budget exhausted ({} calls used); stopping.",
+                    "💸".yellow(),
+                    api_calls_used().to_string().yellow().bold()
+                );
+                let skipped: Vec<&str> = repos[index + 1..]
+                    .iter()
+                    .flatten()
+                    .map(String::as_str)
+                    .collect();
+                if !skipped.is_empty() {
+                    say!(
+                        "{} Repositories not processed: {}",
+                        "📋".yellow(),
+                        skipped.join(", ").yellow()
+                    );
+                }
+                break;
+            },
+            PurgeOutcome::ReserveReached => {
+                say!(
+                    "{} Remaining quota reached the --min-remaining-after reserve; stopping.",
+                    "🛟".yellow()
+                );
+                break;
+            },
+        }
+    }
+
+    let summary_format = args.summary_format.unwrap_or(if repos.len() > 1 {
+        SummaryFormat::Human
+    } else {
+        SummaryFormat::None
+    });
+    if args.command.is_none() {
+        match summary_format {
+            SummaryFormat::Human => {
+                if args.group_repos_by_owner {
+                    let grouped = group_stats_by_owner(
+                        per_repo.iter().map(|(repo, stats)| (repo.as_str(), stats)),
+                    );
+                    for (owner, stats) in &grouped {
+                        if args.compact {
+                            print_compact(&stats.owner_summary(owner), true);
+                        }
+                        say!("{} {}", "👤".cyan(), stats.owner_summary(owner));
+                    }
+                }
+                if args.compact {
+                    print_compact(&totals.global_summary(), true);
+                }
+                say!("{} {}", "🧾".cyan(), totals.global_summary().cyan().bold());
+            },
+            SummaryFormat::Json | SummaryFormat::Toml => {
+                println!("{}", totals.format(summary_format))
+            },
+            SummaryFormat::None => {},
+        }
     }
 
+    HardTimeout::disarm();
+    write_status(&args, ToolPhase::Done);
+    // Only a purge that ran to completion can tell that nothing matched
+    if args.on_empty_exit_code != 0
+        && args.command.is_none()
+        && all_completed
+        && !FOUND_RUNS.load(Ordering::Relaxed)
+    {
+        std::process::exit(args.on_empty_exit_code.into());
+    }
     Ok(())
 }
@@ -253,3 +253,86 @@ fn test_status_filter_whitespace_handling() {
     let result = parse_and_validate_statuses("  success  ,  failure  ").unwrap();
     assert_eq!(result, vec!["success", "failure"]);
 }
+
+#[test]
+fn test_hibernation_with_mocked_rate_limit() {
+    // Scenario: Rate limit injected through the environment instead of calling gh.
+    // No other test in this binary reads these variables, so setting them is race-free.
+    unsafe {
+        std::env::set_var(MOCK_REMAINING_ENV, "10");
+        std::env::set_var(MOCK_RESET_ENV, "1706515800");
+    }
+
+    let rate_limit = check_rate_limit().unwrap();
+
+    unsafe {
+        std::env::remove_var(MOCK_REMAINING_ENV);
+        std::env::remove_var(MOCK_RESET_ENV);
+    }
+
+    assert_eq!(rate_limit.remaining, 10);
+    assert!(should_hibernate(rate_limit.remaining, 50));
+    assert_eq!(reset_wait_seconds(rate_limit.reset, 1706515200), Some(600));
+}
+
+/// Runs the binary against a stub `gh` that knows no pull requests
+#[cfg(unix)]
+#[test]
+fn test_unknown_pr_exits_with_error() {
+    use std::{
+        os::unix::fs::PermissionsExt,
+        process::{Command, Stdio},
+        time::{Duration, Instant},
+    };
+
+    let dir = std::env::temp_dir().join(format!("gh-jobs-purge-stub-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let gh = dir.join("gh");
+    std::fs::write(
+        &gh,
+        "#!/bin/sh\n\
+         case \"$*\" in\n\
+         *--version*) echo 'gh version 2.60.0 (2024-01-01)' ;;\n\
+         *'pr view'*) echo 'no pull requests found' >&2; exit 1 ;;\n\
+         *) echo '[]' ;;\n\
+         esac\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&gh, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_gh-jobs-purge"))
+        .args(["--pr", "999999", "-R", "owner/repo", "--dry-run"])
+        .args(["--simulate-rate-limit", "5000"])
+        .env("PATH", path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Before the fix an unknown PR was retried every 5 seconds forever
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if Instant::now() > deadline {
+            child.kill().unwrap();
+            panic!("gh-jobs-purge kept running after an unknown --pr");
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    };
+    let mut stderr = String::new();
+    std::io::Read::read_to_string(&mut child.stderr.take().unwrap(), &mut stderr).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert!(!status.success());
+    assert!(
+        stderr.contains("Pull request #999999"),
+        "stderr: {}",
+        stderr
+    );
+}